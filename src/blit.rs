@@ -0,0 +1,261 @@
+use crate::shape::BoxRegion;
+use crate::storage::{Storage, StorageMut};
+use crate::XDBuf;
+
+impl<T, const D: usize, S: StorageMut<T>> XDBuf<T, D, S> {
+    /// Copies the region of `src` starting at `src_origin` with `extent` into this buffer starting at `dst_origin`.
+    ///
+    /// The region is clipped against both buffers, and the parts outside either of them are skipped.
+    /// When both buffers have the same memory layout, each innermost lane is copied with a single `copy_from_slice`.
+    ///
+    /// `src`の`src_origin`から始まる大きさ`extent`の領域を、このバッファの`dst_origin`から始まる位置に複製します。
+    ///
+    /// 領域は両方のバッファで切り詰められ、どちらかの外側になる部分は無視されます。
+    /// 両方のバッファが同じメモリ配置を持つ場合、最も内側の列はそれぞれ1回の`copy_from_slice`で複製されます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let tile = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// let mut map = XDBuf::<u8, 2>::new([3, 3], 0).unwrap();
+    ///
+    /// // 右下にはみ出す部分は切り詰められる
+    /// map.blit(&tile, [0, 0], [2, 2], [2, 1]);
+    ///
+    /// assert_eq!(map.as_slice(), &[
+    ///     0, 0, 0,
+    ///     0, 0, 1,
+    ///     0, 0, 3,
+    /// ]);
+    /// ```
+    pub fn blit<S2: Storage<T>>(&mut self, src: &XDBuf<T, D, S2>, src_origin: [isize; D], extent: [usize; D], dst_origin: [isize; D])
+    where
+        T: Copy,
+    {
+        let Some((src_start, dst_start, extent)) = clip(src.size, src_origin, extent, self.size, dst_origin) else {
+            return;
+        };
+
+        let axis = self.layout.axes_fast_to_slow::<D>()[0];
+        let len = extent[axis];
+
+        for offset in lanes(extent, axis) {
+            let s = scalar(&src_start, &offset, &src.stride);
+            let d = scalar(&dst_start, &offset, &self.stride);
+
+            if src.stride[axis] == 1 {
+                self.as_mut_slice()[d..d + len].copy_from_slice(&src.as_slice()[s..s + len]);
+            } else {
+                for k in 0..len {
+                    self.as_mut_slice()[d + k] = src.as_slice()[s + k * src.stride[axis]];
+                }
+            }
+        }
+    }
+
+    /// Combines the region of `src` starting at `src_origin` with `extent` into this buffer starting at `dst_origin` using `f`.
+    ///
+    /// `f` receives the destination element and the corresponding source element, which makes it usable for blending or masking.
+    /// The region is clipped against both buffers in the same way as `blit`.
+    ///
+    /// `src`の`src_origin`から始まる大きさ`extent`の領域を、`f`を用いてこのバッファの`dst_origin`から始まる位置に合成します。
+    ///
+    /// `f`は複製先の要素と対応する複製元の要素を受け取るため、ブレンドやマスクに使用できます。
+    /// 領域は`blit`と同様に両方のバッファで切り詰められます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// // 0は透明
+    /// let sprite = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![0, 7, 7, 0]).unwrap();
+    /// let mut map = XDBuf::<u8, 2>::new([2, 2], 1).unwrap();
+    ///
+    /// map.blit_with(&sprite, [0, 0], [2, 2], [0, 0], |d, &s| if s != 0 { *d = s });
+    ///
+    /// assert_eq!(map.as_slice(), &[1, 7, 7, 1]);
+    /// ```
+    pub fn blit_with<U, S2: Storage<U>>(
+        &mut self,
+        src: &XDBuf<U, D, S2>,
+        src_origin: [isize; D],
+        extent: [usize; D],
+        dst_origin: [isize; D],
+        f: impl Fn(&mut T, &U),
+    ) {
+        let Some((src_start, dst_start, extent)) = clip(src.size, src_origin, extent, self.size, dst_origin) else {
+            return;
+        };
+
+        let axis = self.layout.axes_fast_to_slow::<D>()[0];
+
+        for offset in lanes(extent, axis) {
+            let s = scalar(&src_start, &offset, &src.stride);
+            let d = scalar(&dst_start, &offset, &self.stride);
+
+            for k in 0..extent[axis] {
+                f(&mut self.as_mut_slice()[d + k], &src.as_slice()[s + k * src.stride[axis]]);
+            }
+        }
+    }
+
+    /// Copies the region of this buffer starting at `src_origin` with `extent` to `dst_origin` within the same buffer.
+    ///
+    /// The source and destination regions may overlap, and the result is as if the source region had been copied to a temporary buffer first.
+    /// The region is clipped in the same way as `blit`.
+    ///
+    /// このバッファの`src_origin`から始まる大きさ`extent`の領域を、同じバッファの`dst_origin`に複製します。
+    ///
+    /// 複製元と複製先の領域は重なっていてもよく、結果は複製元の領域を一時的なバッファに複製してから書き込んだ場合と同じになります。
+    /// 領域は`blit`と同様に切り詰められます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new_with_vec([3, 3], vec![
+    ///     1, 2, 0,
+    ///     3, 4, 0,
+    ///     0, 0, 0,
+    /// ]).unwrap();
+    ///
+    /// buf.blit_within([0, 0], [2, 2], [1, 1]);
+    ///
+    /// assert_eq!(buf.as_slice(), &[
+    ///     1, 2, 0,
+    ///     3, 1, 2,
+    ///     0, 3, 4,
+    /// ]);
+    /// ```
+    pub fn blit_within(&mut self, src_origin: [isize; D], extent: [usize; D], dst_origin: [isize; D])
+    where
+        T: Copy,
+    {
+        let Some((src_start, dst_start, extent)) = clip(self.size, src_origin, extent, self.size, dst_origin) else {
+            return;
+        };
+
+        let axis = self.layout.axes_fast_to_slow::<D>()[0];
+        let len = extent[axis];
+
+        let mut pairs = lanes(extent, axis)
+            .map(|offset| (scalar(&src_start, &offset, &self.stride), scalar(&dst_start, &offset, &self.stride)))
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+
+        // 前方に複製する場合は後ろの列から処理して、未処理の複製元を上書きしないようにする
+        if pairs.first().is_some_and(|&(s, d)| d > s) {
+            pairs.reverse();
+        }
+
+        for (s, d) in pairs {
+            self.as_mut_slice().copy_within(s..s + len, d);
+        }
+    }
+}
+
+/// Clips a region of `extent` placed at `src_origin` in a buffer of `src_size` and at `dst_origin` in a buffer of `dst_size`.
+///
+/// Returns the start of the clipped region in both buffers and its extent, or `None` if nothing remains.
+///
+/// `src_size`のバッファの`src_origin`と`dst_size`のバッファの`dst_origin`に置かれた大きさ`extent`の領域を切り詰めます。
+///
+/// 両方のバッファでの切り詰めた領域の始点と大きさを返し、何も残らない場合は`None`を返します。
+fn clip<const D: usize>(
+    src_size: [usize; D],
+    src_origin: [isize; D],
+    extent: [usize; D],
+    dst_size: [usize; D],
+    dst_origin: [isize; D],
+) -> Option<([usize; D], [usize; D], [usize; D])> {
+    let mut clipped = ([0; D], [0; D], [0; D]);
+
+    for a in 0..D {
+        let signed = |v: usize| isize::try_from(v).unwrap_or(isize::MAX);
+
+        let lo = 0_isize.max(src_origin[a].saturating_neg()).max(dst_origin[a].saturating_neg());
+        let hi = signed(extent[a])
+            .min(signed(src_size[a]).saturating_sub(src_origin[a]))
+            .min(signed(dst_size[a]).saturating_sub(dst_origin[a]));
+
+        if hi <= lo {
+            return None;
+        }
+
+        clipped.0[a] = (src_origin[a] + lo) as usize;
+        clipped.1[a] = (dst_origin[a] + lo) as usize;
+        clipped.2[a] = (hi - lo) as usize;
+    }
+
+    Some(clipped)
+}
+
+/// Returns the offsets of the starts of the lanes along `axis` in a region of `extent`.
+///
+/// 大きさ`extent`の領域にある、`axis`に沿った列の始点のオフセットを返します。
+fn lanes<const D: usize>(extent: [usize; D], axis: usize) -> BoxRegion<D> {
+    let mut max = extent.map(|e| e as isize - 1);
+    max[axis] = 0;
+
+    BoxRegion::new(extent, [0; D], max)
+}
+
+fn scalar<const D: usize>(start: &[usize; D], offset: &[usize; D], stride: &[usize; D]) -> usize {
+    (0..D).map(|a| (start[a] + offset[a]) * stride[a]).sum()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn blit_between_layouts_matches_elementwise_copy() {
+        let src = XDBuf::<i32, 3>::new_with_layout([4, 3, 5], 0, Layout::LastMajor).unwrap()
+            .map_indexed(|[x, y, z], _| (x * 100 + y * 10 + z) as i32);
+        let mut dst = XDBuf::<i32, 3>::new([5, 5, 5], -1).unwrap();
+
+        dst.blit(&src, [1, -1, 2], [4, 4, 4], [-1, 2, 0]);
+
+        for (index, &v) in dst.indexed_iter() {
+            let s = [index[0] as isize + 2, index[1] as isize - 3, index[2] as isize + 2];
+            let inside = index[0] < 3 && (2..5).contains(&index[1]) && index[2] < 4
+                && s.iter().zip(src.size()).all(|(&s, n)| (0..n as isize).contains(&s));
+
+            let expected = if inside { *src.get_m(s.map(|s| s as usize)).unwrap() } else { -1 };
+            assert_eq!(v, expected, "{:?}", index);
+        }
+    }
+
+    #[test]
+    fn blit_within_overlapping_in_both_directions() {
+        for (from, to) in [([0, 0], [1, 2]), ([1, 2], [0, 0]), ([2, 0], [0, 1])] {
+            let mut buf = XDBuf::<u16, 2>::new([6, 5], 0).unwrap().map_indexed(|[x, y], _| (x + 10 * y) as u16);
+            let original = buf.clone();
+
+            buf.blit_within(from, [4, 3], to);
+
+            let mut expected = original.clone();
+            expected.blit(&original, from, [4, 3], to);
+            assert_eq!(buf.as_slice(), expected.as_slice());
+        }
+    }
+
+    #[test]
+    fn blit_outside_is_noop() {
+        let src = XDBuf::<u8, 2>::new([2, 2], 1).unwrap();
+        let mut dst = XDBuf::<u8, 2>::new([2, 2], 0).unwrap();
+
+        dst.blit(&src, [0, 0], [2, 2], [2, 0]);
+        dst.blit(&src, [-2, 0], [2, 2], [0, 0]);
+        dst.blit(&src, [0, 0], [0, 2], [0, 0]);
+        dst.blit_with(&src, [isize::MIN, 0], [usize::MAX, 2], [isize::MAX, 0], |d, &s| *d = s);
+
+        assert_eq!(dst.as_slice(), &[0, 0, 0, 0]);
+    }
+}
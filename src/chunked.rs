@@ -0,0 +1,495 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::anyhow;
+
+use crate::XDBuf;
+
+/// Callback invoked with the coordinate and contents of an evicted chunk.
+///
+/// 追い出されたチャンクの座標と内容を受け取るコールバックです。
+type EvictFn<T, const D: usize> = Box<dyn FnMut([i64; D], XDBuf<T, D>) + Send>;
+
+/// Structure representing an unbounded n-dimensional grid made of chunks of `C^D` elements
+///
+/// Chunks are allocated on the first write to one of their elements. Unloaded elements read as the default value.
+/// Chunk coordinates are the element coordinates divided by `C`, rounded toward negative infinity.
+///
+/// `C^D`個の要素からなるチャンクで構成される、範囲に制限のないn次元のグリッドを表す構造体です。
+///
+/// チャンクはその要素に最初に書き込んだ時点で割り当てられます。読み込まれていない要素は既定値として読み出されます。
+/// チャンクの座標は、要素の座標を`C`で割って負の無限大方向に丸めたものです。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::ChunkedGrid;
+///
+/// let mut grid = ChunkedGrid::<u8, 2, 16>::new(0).unwrap();
+///
+/// grid.set([-1_000_000, 42], 7);
+/// assert_eq!(grid.get([-1_000_000, 42]), &7);
+/// assert_eq!(grid.get([5, 5]), &0);
+/// assert_eq!(grid.loaded_len(), 1);
+/// ```
+pub struct ChunkedGrid<T, const D: usize, const C: usize> {
+    chunks: HashMap<[i64; D], XDBuf<T, D>>,
+    default: T,
+    on_evict: Option<EvictFn<T, D>>,
+}
+
+impl<T: fmt::Debug, const D: usize, const C: usize> fmt::Debug for ChunkedGrid<T, D, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedGrid")
+            .field("chunks", &self.chunks)
+            .field("default", &self.default)
+            .field("on_evict", &self.on_evict.is_some())
+            .finish()
+    }
+}
+
+impl<T, const D: usize, const C: usize> ChunkedGrid<T, D, C> {
+    /// Generate a new `ChunkedGrid` with no chunks loaded.
+    ///
+    /// Every element reads as `default` until it is written.
+    ///
+    /// チャンクが読み込まれていない新しい`ChunkedGrid`を生成します。
+    ///
+    /// 各要素は書き込まれるまで`default`として読み出されます。
+    ///
+    /// `C` must be between 1 and `i64::MAX`, which is checked at compile time.
+    ///
+    /// `C`は1以上`i64::MAX`以下である必要があり、コンパイル時にチェックされます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `C^D` exceeds the range of `usize`.
+    ///
+    /// * `C^D`が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::ChunkedGrid;
+    ///
+    /// assert!(ChunkedGrid::<u8, 3, 32>::new(0).is_ok());
+    /// assert!(ChunkedGrid::<u8, 8, 1024>::new(0).is_err());
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use xdbuf::ChunkedGrid;
+    ///
+    /// let grid = ChunkedGrid::<u8, 3, 0>::new(0); // compile error!
+    /// ```
+    pub fn new(default: T) -> Result<Self, anyhow::Error> {
+        const { assert!(C > 0 && C <= i64::MAX as usize, "chunk size is out of range") };
+
+        XDBuf::<T, D>::calc_total_size(&[C; D]).map_err(|_| anyhow!("chunk size is out of range"))?;
+
+        Ok(Self {
+            chunks: HashMap::new(),
+            default,
+            on_evict: None,
+        })
+    }
+
+    /// Sets a callback invoked with the coordinate and contents of each evicted chunk, such as for saving it to disk.
+    ///
+    /// 追い出された各チャンクの座標と内容を受け取るコールバックを設定します。ディスクへの保存などに使用します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use xdbuf::ChunkedGrid;
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    ///
+    /// let mut grid = ChunkedGrid::<u8, 2, 8>::new(0).unwrap();
+    /// grid.set_on_evict(move |chunk, buf| tx.send((chunk, buf)).unwrap());
+    ///
+    /// grid.set([-1, 3], 5);
+    /// grid.evict([-1, 0]);
+    ///
+    /// let (chunk, buf) = rx.recv().unwrap();
+    /// assert_eq!(chunk, [-1, 0]);
+    /// assert_eq!(buf.get_m([7, 3]), Some(&5));
+    /// ```
+    pub fn set_on_evict(&mut self, f: impl FnMut([i64; D], XDBuf<T, D>) + Send + 'static) {
+        self.on_evict = Some(Box::new(f));
+    }
+
+    /// Convert element coordinates to the coordinate of the containing chunk and the index in array notation within it.
+    ///
+    /// `C` must be between 1 and `i64::MAX`, which is checked at compile time.
+    ///
+    /// 要素の座標を、それを含むチャンクの座標とチャンク内の配列表記のインデックスに変換します。
+    ///
+    /// `C`は1以上`i64::MAX`以下である必要があり、コンパイル時にチェックされます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::ChunkedGrid;
+    ///
+    /// let (chunk, local) = ChunkedGrid::<u8, 2, 16>::to_chunk_index([-1, 35]);
+    /// assert_eq!(chunk, [-1, 2]);
+    /// assert_eq!(local, [15, 3]);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use xdbuf::ChunkedGrid;
+    ///
+    /// let (chunk, local) = ChunkedGrid::<u8, 2, 0>::to_chunk_index([-1, 35]); // compile error!
+    /// ```
+    pub fn to_chunk_index(point: [i64; D]) -> ([i64; D], [usize; D]) {
+        const { assert!(C > 0 && C <= i64::MAX as usize, "chunk size is out of range") };
+
+        // 上のアサーションにより`C`は`i64`に収まる
+        let c = C as i64;
+
+        (point.map(|p| p.div_euclid(c)), point.map(|p| p.rem_euclid(c) as usize))
+    }
+
+    /// Get a reference to the element at `point`.
+    ///
+    /// Returns the default value if the containing chunk is not loaded.
+    ///
+    /// `point`の要素の参照を取得します。
+    ///
+    /// 含むチャンクが読み込まれていない場合は既定値を返します。
+    pub fn get(&self, point: [i64; D]) -> &T {
+        let (chunk, local) = Self::to_chunk_index(point);
+
+        self.chunks.get(&chunk).and_then(|buf| buf.get_m(local)).unwrap_or(&self.default)
+    }
+
+    /// Get a variable reference to the element at `point`, allocating the containing chunk if it is not loaded.
+    ///
+    /// `point`の要素の可変参照を取得します。含むチャンクが読み込まれていない場合は割り当てます。
+    pub fn get_mut(&mut self, point: [i64; D]) -> &mut T
+    where
+        T: Clone,
+    {
+        let (chunk, local) = Self::to_chunk_index(point);
+
+        // 要素は必ずチャンク内にある
+        self.chunk_or_insert(chunk).get_mut_m(local).unwrap()
+    }
+
+    /// Set `value` to the element at `point`, allocating the containing chunk if it is not loaded.
+    ///
+    /// `point`の要素に`value`を設定します。含むチャンクが読み込まれていない場合は割り当てます。
+    pub fn set(&mut self, point: [i64; D], value: T)
+    where
+        T: Clone,
+    {
+        *self.get_mut(point) = value;
+    }
+
+    /// Returns the default value of unloaded elements.
+    ///
+    /// 読み込まれていない要素の既定値を返します。
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    /// Returns `true` if the chunk at `chunk` is loaded.
+    ///
+    /// `chunk`のチャンクが読み込まれている場合に`true`を返します。
+    pub fn is_loaded(&self, chunk: [i64; D]) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    /// Returns the number of loaded chunks.
+    ///
+    /// 読み込まれているチャンクの数を返します。
+    pub fn loaded_len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Get a reference to the chunk at `chunk`.
+    ///
+    /// Returns `None` if the chunk is not loaded.
+    ///
+    /// `chunk`のチャンクの参照を取得します。
+    ///
+    /// チャンクが読み込まれていない場合は`None`を返します。
+    pub fn chunk(&self, chunk: [i64; D]) -> Option<&XDBuf<T, D>> {
+        self.chunks.get(&chunk)
+    }
+
+    /// Get a variable reference to the chunk at `chunk`.
+    ///
+    /// Returns `None` if the chunk is not loaded.
+    ///
+    /// `chunk`のチャンクの可変参照を取得します。
+    ///
+    /// チャンクが読み込まれていない場合は`None`を返します。
+    pub fn chunk_mut(&mut self, chunk: [i64; D]) -> Option<&mut XDBuf<T, D>> {
+        self.chunks.get_mut(&chunk)
+    }
+
+    /// Loads `buf` as the chunk at `chunk`, such as one restored from disk, and returns the chunk it replaces.
+    ///
+    /// ディスクから復元したものなどの`buf`を`chunk`のチャンクとして読み込み、置き換えられたチャンクを返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the size of `buf` is not `C` in every dimension.
+    ///
+    /// * `buf`のサイズがすべての次元で`C`でない場合エラーになります。
+    pub fn insert_chunk(&mut self, chunk: [i64; D], buf: XDBuf<T, D>) -> Result<Option<XDBuf<T, D>>, anyhow::Error> {
+        if buf.size() != [C; D] {
+            return Err(anyhow!("chunk size must be {:?}, but is {:?}", [C; D], buf.size()));
+        }
+
+        Ok(self.chunks.insert(chunk, buf))
+    }
+
+    /// Unloads the chunk at `chunk`, passing it to the eviction callback.
+    ///
+    /// Returns `true` if the chunk was loaded.
+    ///
+    /// `chunk`のチャンクを解放し、追い出し時のコールバックに渡します。
+    ///
+    /// チャンクが読み込まれていた場合は`true`を返します。
+    pub fn evict(&mut self, chunk: [i64; D]) -> bool {
+        match self.chunks.remove(&chunk) {
+            Some(buf) => {
+                if let Some(on_evict) = &mut self.on_evict {
+                    on_evict(chunk, buf);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Keeps only the chunks for which `f` returns `true`, passing the others to the eviction callback.
+    ///
+    /// `f`が`true`を返すチャンクのみを残し、それ以外を追い出し時のコールバックに渡します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::ChunkedGrid;
+    ///
+    /// let mut grid = ChunkedGrid::<u8, 2, 8>::new(0).unwrap();
+    /// for x in -10..10 {
+    ///     grid.set([x * 8, 0], 1);
+    /// }
+    ///
+    /// // プレイヤーから離れたチャンクを解放
+    /// let player = [0_i64, 0];
+    /// grid.retain_chunks(|chunk, _| (chunk[0] - player[0]).abs() <= 2);
+    /// assert_eq!(grid.loaded_len(), 5);
+    /// ```
+    pub fn retain_chunks(&mut self, mut f: impl FnMut(&[i64; D], &XDBuf<T, D>) -> bool) {
+        let evicted = self.chunks.iter()
+            .filter(|(chunk, buf)| !f(chunk, buf))
+            .map(|(&chunk, _)| chunk)
+            .collect::<Vec<_>>();
+
+        evicted.into_iter().for_each(|chunk| {
+            self.evict(chunk);
+        });
+    }
+
+    /// Returns an iterator over the coordinates and contents of the loaded chunks in arbitrary order.
+    ///
+    /// 読み込まれているチャンクの座標と内容を任意の順序で走査するイテレータを返します。
+    pub fn chunks(&self) -> impl Iterator<Item = (&[i64; D], &XDBuf<T, D>)> + '_ {
+        self.chunks.iter()
+    }
+
+    /// Returns an iterator that allows modifying the loaded chunks in arbitrary order.
+    ///
+    /// 読み込まれているチャンクを任意の順序で変更できるイテレータを返します。
+    pub fn chunks_mut(&mut self) -> impl Iterator<Item = (&[i64; D], &mut XDBuf<T, D>)> + '_ {
+        self.chunks.iter_mut()
+    }
+
+    /// Generates a `ChunkedWalker` with `point` as its initial position.
+    ///
+    /// `point`を初期位置として`ChunkedWalker`を生成します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{step2d, ChunkedGrid};
+    ///
+    /// let mut grid = ChunkedGrid::<u8, 2, 4>::new(0).unwrap();
+    /// grid.set([-1, 0], 9);
+    ///
+    /// // チャンクの境界を越えて移動
+    /// let mut walker = grid.walker_from([0, 0]);
+    /// walker.as_(&step2d::LEFT).unwrap();
+    /// assert_eq!(walker.index(), [-1, 0]);
+    /// assert_eq!(walker.get(), &9);
+    /// ```
+    pub fn walker_from(&self, point: [i64; D]) -> ChunkedWalker<'_, T, D, C> {
+        ChunkedWalker {
+            grid: self,
+            current_index: point,
+        }
+    }
+
+    fn chunk_or_insert(&mut self, chunk: [i64; D]) -> &mut XDBuf<T, D>
+    where
+        T: Clone,
+    {
+        let default = &self.default;
+
+        // チャンクのサイズは`new`で検証済み
+        self.chunks.entry(chunk).or_insert_with(|| XDBuf::new([C; D], default.clone()).unwrap())
+    }
+}
+
+/// Structure for index operations on a `ChunkedGrid` that crosses chunk boundaries transparently
+///
+/// Steps such as those in `step2d` and `step3d` can be used as with a `Walker`.
+///
+/// チャンクの境界を意識せずに`ChunkedGrid`におけるインデックス操作を行うための構造体
+///
+/// `Walker`と同様に`step2d`や`step3d`などの移動量を使用できます。
+#[derive(Debug)]
+pub struct ChunkedWalker<'a, T, const D: usize, const C: usize> {
+    grid: &'a ChunkedGrid<T, D, C>,
+    current_index: [i64; D],
+}
+
+impl<T, const D: usize, const C: usize> Clone for ChunkedWalker<'_, T, D, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, const D: usize, const C: usize> Copy for ChunkedWalker<'_, T, D, C> {}
+
+impl<'a, T, const D: usize, const C: usize> ChunkedWalker<'a, T, D, C> {
+    /// Returns the current element coordinates.
+    ///
+    /// 現在の要素の座標を返します。
+    pub fn index(&self) -> [i64; D] {
+        self.current_index
+    }
+
+    /// Returns a reference to the current element.
+    ///
+    /// 現在の要素の参照を返します。
+    pub fn get(&self) -> &'a T {
+        self.grid.get(self.current_index)
+    }
+
+    /// Returns the current element coordinates plus `step`.
+    ///
+    /// 現在の要素の座標に`step`を加算した座標を返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination coordinates exceed the range of `i64`.
+    ///
+    /// * 移動先の座標が`i64`の範囲を超える場合エラーになります。
+    pub fn index_(&self, step: &[isize; D]) -> Result<[i64; D], anyhow::Error> {
+        let mut index = self.current_index;
+
+        index.iter_mut().zip(step).try_for_each(|(current_index, &step)| {
+            *current_index = i64::try_from(step).ok()
+                .and_then(|step| current_index.checked_add(step))
+                .ok_or(anyhow!("Index out of range"))?;
+
+            Ok::<_, anyhow::Error>(())
+        })?;
+
+        Ok(index)
+    }
+
+    /// Moves to the current element coordinates plus `step`.
+    ///
+    /// 現在の要素の座標に`step`を加算した座標に移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination coordinates exceed the range of `i64`.
+    ///
+    /// * 移動先の座標が`i64`の範囲を超える場合エラーになります。
+    pub fn as_(&mut self, step: &[isize; D]) -> Result<&mut Self, anyhow::Error> {
+        self.current_index = self.index_(step)?;
+        Ok(self)
+    }
+
+    /// Moves to the current element coordinates plus `step`.
+    ///
+    /// 現在の要素の座標に`step`を加算した座標に移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination coordinates exceed the range of `i64`.
+    ///
+    /// * 移動先の座標が`i64`の範囲を超える場合エラーになります。
+    pub fn into_(mut self, step: &[isize; D]) -> Result<Self, anyhow::Error> {
+        self.as_(step)?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use crate::step3d;
+
+    use super::*;
+
+    #[test]
+    fn chunks_are_allocated_on_first_write_only() {
+        let mut grid = ChunkedGrid::<i32, 3, 4>::new(-1).unwrap();
+
+        assert_eq!(grid.get([100, -100, 3]), &-1);
+        assert_eq!(grid.loaded_len(), 0);
+
+        grid.set([-4, -1, 0], 2);
+        grid.set([-1, -4, 3], 3);
+        assert_eq!(grid.loaded_len(), 1);
+        assert!(grid.is_loaded([-1, -1, 0]));
+        assert_eq!(grid.chunk([-1, -1, 0]).unwrap().get_m([3, 0, 3]), Some(&3));
+    }
+
+    #[test]
+    fn walker_crosses_chunk_boundaries() {
+        let mut grid = ChunkedGrid::<usize, 3, 2>::new(0).unwrap();
+        for x in -3..3 {
+            grid.set([x, 0, 0], (x + 3) as usize);
+        }
+
+        let mut walker = grid.walker_from([2, 0, 0]);
+        let mut visited = vec![*walker.get()];
+        while walker.index()[0] > -3 {
+            walker.as_(&step3d::LEFT).unwrap();
+            visited.push(*walker.get());
+        }
+
+        assert_eq!(visited, vec![5, 4, 3, 2, 1, 0]);
+        assert_eq!(walker.into_(&step3d::TOP).unwrap().get(), &0);
+        assert!(grid.walker_from([i64::MAX, 0, 0]).index_(&step3d::RIGHT).is_err());
+    }
+
+    #[test]
+    fn retain_chunks_passes_evicted_chunks_to_callback() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+
+        let mut grid = ChunkedGrid::<u8, 2, 8>::new(0).unwrap();
+        let sink = evicted.clone();
+        grid.set_on_evict(move |chunk, _| sink.lock().unwrap().push(chunk));
+
+        grid.set([0, 0], 1);
+        grid.set([-8, 0], 1);
+        grid.set([16, 0], 1);
+        grid.retain_chunks(|chunk, _| chunk[0] >= 0);
+
+        assert_eq!(*evicted.lock().unwrap(), vec![[-1, 0]]);
+        assert_eq!(grid.loaded_len(), 2);
+        assert!(!grid.evict([-1, 0]));
+    }
+}
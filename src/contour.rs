@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use num_traits::Float;
+
+use crate::storage::Storage;
+use crate::XDBuf;
+
+/// Sequence of connected points forming a contour line
+///
+/// 等値線を構成する、連結された点の列
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Polyline {
+    /// The points of the line. For a closed line, the first point is not repeated at the end.
+    ///
+    /// 線の点。閉じた線では、最初の点は末尾で繰り返されません。
+    pub points: Vec<[f64; 2]>,
+    /// Whether the last point is connected back to the first one.
+    ///
+    /// 最後の点が最初の点に接続されているか。
+    pub closed: bool,
+}
+
+/// Edge of the grid between two neighbouring elements, as `(y, x, axis)` of its first element and direction
+///
+/// 隣り合う2つの要素の間にあるグリッドの辺。最初の要素と方向の`(y, x, axis)`で表します。
+type EdgeKey = (usize, usize, usize);
+
+impl<T: Float, S: Storage<T>> XDBuf<T, 2, S> {
+    /// Extracts the lines where the values cross `level` with marching squares.
+    ///
+    /// The element at index `[x, y]` is located at position `[x, y]`, and the crossing points are placed by linear interpolation along the edges.
+    /// Elements whose value is greater than or equal to `level` are inside. Ambiguous saddle cells are resolved with the average of their four corners.
+    /// Cells with a NaN corner are skipped, so lines reaching them end there and are open.
+    /// Lines ending at the boundary of the buffer are open, and the others are closed.
+    /// Use `contours_world` to place the points in world coordinates.
+    ///
+    /// マーチングスクエア法で、値が`level`と交差する線を抽出します。
+    ///
+    /// インデックス`[x, y]`の要素は位置`[x, y]`にあり、交点は辺に沿った線形補間で配置されます。
+    /// 値が`level`以上の要素が内側になります。曖昧な鞍点のセルは4つの角の平均で判定されます。
+    /// NaNの角を持つセルは読み飛ばされるため、そこに達する線はそこで終わり、開いた線になります。
+    /// バッファの境界で終わる線は開いており、それ以外は閉じています。
+    /// 点をワールド座標に配置するには`contours_world`を使用します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// // 中央の山
+    /// let heightmap = XDBuf::<f32, 2>::new_with_vec([3, 3], vec![
+    ///     0.0, 0.0, 0.0,
+    ///     0.0, 2.0, 0.0,
+    ///     0.0, 0.0, 0.0,
+    /// ]).unwrap();
+    ///
+    /// let contours = heightmap.contours(1.0);
+    ///
+    /// assert_eq!(contours.len(), 1);
+    /// assert!(contours[0].closed);
+    /// assert_eq!(contours[0].points.len(), 4);
+    /// assert!(contours[0].points.contains(&[1.5, 1.0]));
+    /// ```
+    pub fn contours(&self, level: T) -> Vec<Polyline> {
+        let [w, h] = self.size;
+        let value = |x: usize, y: usize| self.as_slice()[x * self.stride[0] + y * self.stride[1]];
+
+        // 辺上の交点と、交点を結ぶ線分で隣接する交点
+        let mut graph = BTreeMap::<EdgeKey, ([f64; 2], Vec<EdgeKey>)>::new();
+
+        for y in 0..h.saturating_sub(1) {
+            for x in 0..w.saturating_sub(1) {
+                let corners = [value(x, y), value(x + 1, y), value(x + 1, y + 1), value(x, y + 1)];
+
+                // NaNを通る補間では交点が定まらない
+                if corners.iter().any(|v| v.is_nan()) {
+                    continue;
+                }
+                let case = corners.iter().enumerate().fold(0, |acc, (k, &v)| acc | usize::from(v >= level) << k);
+
+                // 下、右、上、左の辺
+                let edges = [(y, x, 0), (y, x + 1, 1), (y + 1, x, 0), (y, x, 1)];
+
+                let center_inside = {
+                    let four = T::one() + T::one() + T::one() + T::one();
+                    corners.iter().fold(T::zero(), |acc, &v| acc + v) / four >= level
+                };
+
+                let segments: &[(usize, usize)] = match case {
+                    0 | 15 => &[],
+                    1 | 14 => &[(3, 0)],
+                    2 | 13 => &[(0, 1)],
+                    3 | 12 => &[(3, 1)],
+                    4 | 11 => &[(1, 2)],
+                    6 | 9 => &[(0, 2)],
+                    7 | 8 => &[(3, 2)],
+                    // 鞍点
+                    5 if center_inside => &[(0, 1), (2, 3)],
+                    5 => &[(3, 0), (1, 2)],
+                    10 if center_inside => &[(3, 0), (1, 2)],
+                    _ => &[(0, 1), (2, 3)],
+                };
+
+                for &(a, b) in segments {
+                    for (from, to) in [(a, b), (b, a)] {
+                        let key = edges[from];
+                        graph.entry(key)
+                            .or_insert_with(|| (self.crossing(key, level), Vec::new()))
+                            .1
+                            .push(edges[to]);
+                    }
+                }
+            }
+        }
+
+        let mut visited = BTreeSet::new();
+        let mut polylines = Vec::new();
+
+        // 端点から始まる開いた線を先に、残りの閉じた線を後に辿る
+        let open = graph.iter().filter(|(_, (_, n))| n.len() == 1).map(|(&k, _)| (k, false)).collect::<Vec<_>>();
+        let all = graph.keys().map(|&k| (k, true)).collect::<Vec<_>>();
+
+        for (start, closed) in open.into_iter().chain(all) {
+            if visited.contains(&start) {
+                continue;
+            }
+
+            let mut points = Vec::new();
+            let mut current = Some(start);
+            while let Some(key) = current {
+                visited.insert(key);
+
+                let (point, neighbours) = &graph[&key];
+                points.push(*point);
+                current = neighbours.iter().copied().find(|n| !visited.contains(n));
+            }
+
+            polylines.push(Polyline { points, closed });
+        }
+
+        polylines
+    }
+
+    /// Extracts the contour lines for each of `levels`.
+    ///
+    /// The result is in the order of `levels`.
+    ///
+    /// `levels`のそれぞれについて等値線を抽出します。
+    ///
+    /// 結果は`levels`の順になります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let slope = XDBuf::<f64, 2>::new([5, 5], 0.0).unwrap().map_indexed(|[x, _], _| x as f64);
+    /// let contour_map = slope.contours_levels(&[0.5, 1.5, 2.5]);
+    ///
+    /// assert_eq!(contour_map.len(), 3);
+    /// for (lines, level) in contour_map.iter().zip([0.5, 1.5, 2.5]) {
+    ///     assert_eq!(lines.len(), 1);
+    ///     assert!(!lines[0].closed);
+    ///     assert!(lines[0].points.iter().all(|p| p[0] == level));
+    /// }
+    /// ```
+    pub fn contours_levels(&self, levels: &[T]) -> Vec<Vec<Polyline>> {
+        levels.iter().map(|&level| self.contours(level)).collect()
+    }
+
+    /// Extracts the lines where the values cross `level`, in world coordinates.
+    ///
+    /// The element at index `[x, y]` is located at `origin + [x, y] * spacing`. Otherwise the same as `contours`.
+    ///
+    /// 値が`level`と交差する線をワールド座標で抽出します。
+    ///
+    /// インデックス`[x, y]`の要素は`origin + [x, y] * spacing`に位置します。それ以外は`contours`と同じです。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// // 西端が経度135度、1要素あたり0.5度の標高図
+    /// let heightmap = XDBuf::<f32, 2>::new_with_vec([3, 3], vec![
+    ///     0.0, 0.0, 0.0,
+    ///     0.0, 2.0, 0.0,
+    ///     0.0, 0.0, 0.0,
+    /// ]).unwrap();
+    ///
+    /// let contours = heightmap.contours_world(1.0, [135.0, 35.0], [0.5, 0.5]);
+    ///
+    /// assert_eq!(contours.len(), 1);
+    /// assert!(contours[0].points.contains(&[135.75, 35.5]));
+    /// ```
+    pub fn contours_world(&self, level: T, origin: [f64; 2], spacing: [f64; 2]) -> Vec<Polyline> {
+        let mut polylines = self.contours(level);
+
+        for p in polylines.iter_mut().flat_map(|l| l.points.iter_mut()) {
+            *p = [origin[0] + p[0] * spacing[0], origin[1] + p[1] * spacing[1]];
+        }
+
+        polylines
+    }
+
+    /// Extracts the contour lines for each of `levels`, in world coordinates.
+    ///
+    /// The element at index `[x, y]` is located at `origin + [x, y] * spacing`. The result is in the order of `levels`.
+    ///
+    /// `levels`のそれぞれについて等値線をワールド座標で抽出します。
+    ///
+    /// インデックス`[x, y]`の要素は`origin + [x, y] * spacing`に位置します。結果は`levels`の順になります。
+    pub fn contours_levels_world(&self, levels: &[T], origin: [f64; 2], spacing: [f64; 2]) -> Vec<Vec<Polyline>> {
+        levels.iter().map(|&level| self.contours_world(level, origin, spacing)).collect()
+    }
+
+    /// Returns the point on the edge `key` where the linearly interpolated value equals `level`.
+    ///
+    /// 辺`key`上で、線形補間した値が`level`と等しくなる点を返します。
+    fn crossing(&self, key: EdgeKey, level: T) -> [f64; 2] {
+        let (y, x, axis) = key;
+        let a = self.as_slice()[x * self.stride[0] + y * self.stride[1]];
+        let b = self.as_slice()[x * self.stride[0] + y * self.stride[1] + self.stride[axis]];
+
+        // 交差する辺では両端の値が異なる
+        let t = ((level - a) / (b - a)).to_f64().unwrap_or(0.5);
+
+        let mut point = [x as f64, y as f64];
+        point[axis] += t;
+        point
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn saddle_is_resolved_by_center() {
+        let mut buf = XDBuf::<f32, 2>::new_with_vec([2, 2], vec![1.0, 0.0, 0.0, 1.0]).unwrap();
+
+        // 中心は内側なので、外側の角が切り離される
+        let lines = buf.contours(0.25);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.points.len() == 2 && !l.closed));
+        assert!(lines.iter().any(|l| l.points.contains(&[0.75, 0.0]) && l.points.contains(&[1.0, 0.25])));
+
+        // 中心が外側になると、内側の角が切り離される
+        buf.set_m([1, 1], 0.5).unwrap();
+        let lines = buf.contours(0.5);
+        assert!(lines.iter().any(|l| l.points.contains(&[0.0, 0.5]) && l.points.contains(&[0.5, 0.0])));
+    }
+
+    #[test]
+    fn contour_points_lie_on_level() {
+        let buf = XDBuf::<f64, 2>::new([12, 9], 0.0).unwrap()
+            .map_indexed(|[x, y], _| ((x as f64 - 5.5).powi(2) + (y as f64 - 4.0).powi(2)).sqrt());
+
+        let lines = buf.contours(3.0);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].closed);
+
+        for p in &lines[0].points {
+            let sampled = buf.sample(*p, crate::Interp::Linear, crate::Boundary::Clamp);
+            assert!((sampled - 3.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn world_coordinates_are_scaled_and_offset() {
+        let buf = XDBuf::<f64, 2>::new([4, 3], 0.0).unwrap().map_indexed(|[x, y], _| (x + y) as f64);
+
+        let grid = buf.contours_levels(&[1.5, 2.5]);
+        let world = buf.contours_levels_world(&[1.5, 2.5], [-100.0, 20.0], [2.0, -0.5]);
+
+        assert_eq!(grid.len(), world.len());
+        for (g, w) in grid.iter().flatten().zip(world.iter().flatten()) {
+            assert_eq!(g.closed, w.closed);
+            for (gp, wp) in g.points.iter().zip(&w.points) {
+                assert_eq!(*wp, [-100.0 + gp[0] * 2.0, 20.0 - gp[1] * 0.5]);
+            }
+        }
+    }
+
+    #[test]
+    fn cells_with_nan_corners_are_skipped() {
+        let buf = XDBuf::<f32, 2>::new_with_vec([3, 3], vec![
+            f32::NAN, 0.0, 0.0,
+            0.0, 2.0, 0.0,
+            0.0, 0.0, 0.0,
+        ]).unwrap();
+
+        let lines = buf.contours(1.0);
+        assert_eq!(lines.len(), 1);
+        assert!(!lines[0].closed);
+        assert_eq!(lines[0].points.len(), 4);
+        assert!(lines[0].points.iter().flatten().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn flat_or_thin_buffers_have_no_contours() {
+        assert!(XDBuf::<f32, 2>::new([4, 4], 1.0).unwrap().contours(1.0).is_empty());
+        assert!(XDBuf::<f32, 2>::new_with_vec([3, 1], vec![0.0, 1.0, 0.0]).unwrap().contours(0.5).is_empty());
+    }
+}
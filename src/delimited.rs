@@ -0,0 +1,279 @@
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::storage::Storage;
+use crate::XDBuf;
+
+impl<T> XDBuf<T, 2> {
+    /// Generate a 2-dimensional `XDBuf` from delimited text such as CSV.
+    ///
+    /// Each line becomes a row (the second dimension) and each field becomes a column (the first dimension).
+    /// Fields are trimmed of surrounding whitespace before parsing, and empty lines are skipped.
+    /// If `delimiter` is whitespace, any run of whitespace separates fields, as in column-aligned text.
+    ///
+    /// 区切り文字で区切られたテキスト(CSVなど)から2次元の`XDBuf`を生成します。
+    ///
+    /// 各行が2番目の次元に、各フィールドが1番目の次元に対応します。
+    /// フィールドは前後の空白を取り除いてからパースされ、空行は読み飛ばされます。
+    /// `delimiter`が空白文字の場合は、桁揃えされたテキストのように連続する空白でフィールドが区切られます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if reading from `reader` fails.
+    /// * Error if a field cannot be parsed as `T`.
+    /// * Error if a line has a different number of fields than the first line.
+    /// * Error if there are no rows.
+    ///
+    /// * `reader`からの読み込みに失敗した場合エラーになります。
+    /// * フィールドを`T`としてパースできない場合エラーになります。
+    /// * 最初の行とフィールド数が異なる行がある場合エラーになります。
+    /// * 行が存在しない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let text = "1,2,3\n4,5,6\n";
+    /// let buf = XDBuf::<i32, 2>::from_delimited(text.as_bytes(), ',').unwrap();
+    ///
+    /// assert_eq!(buf.size(), [3, 2]);
+    /// assert_eq!(buf.get(buf.to_scalar_index(&[2, 1]).unwrap()), Some(&6));
+    /// ```
+    ///
+    /// ```should_panic
+    /// use xdbuf::XDBuf;
+    ///
+    /// let text = "1,2,3\n4,5\n"; // line 2 has only 2 fields
+    /// let buf = XDBuf::<i32, 2>::from_delimited(text.as_bytes(), ',').unwrap(); // panic!
+    /// ```
+    pub fn from_delimited<R: BufRead>(reader: R, delimiter: char) -> Result<Self, anyhow::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let mut buf = Vec::new();
+        let mut columns = None;
+        let mut rows = 0_usize;
+
+        for (line_number, line) in reader.lines().enumerate().map(|(i, l)| (i + 1, l)) {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Box<dyn Iterator<Item = &str>> = if delimiter.is_whitespace() {
+                Box::new(line.split_whitespace())
+            } else {
+                Box::new(line.split(delimiter))
+            };
+
+            let len_before = buf.len();
+            for field in fields {
+                let value = field.trim().parse::<T>().map_err(|e| {
+                    anyhow!("failed to parse field {:?} at line {}: {}", field, line_number, e)
+                })?;
+                buf.push(value);
+            }
+
+            let len = buf.len() - len_before;
+            match columns {
+                None => columns = Some(len),
+                Some(columns) if columns != len => {
+                    return Err(anyhow!(
+                        "line {} has {} fields, but {} fields were expected", line_number, len, columns
+                    ));
+                }
+                _ => {}
+            }
+
+            rows += 1;
+        }
+
+        let columns = columns.ok_or(anyhow!("no rows were found"))?;
+
+        Self::new_with_vec([columns, rows], buf)
+    }
+}
+
+impl<T, S: Storage<T>> XDBuf<T, 2, S> {
+    /// Write the buffer as delimited text.
+    ///
+    /// Each row (the second dimension) is written as one line.
+    ///
+    /// バッファを区切り文字で区切られたテキストとして書き出します。
+    ///
+    /// 2番目の次元の各行が1行として書き出されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if writing to `writer` fails.
+    ///
+    /// * `writer`への書き込みに失敗した場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([3, 2], vec![1, 2, 3, 4, 5, 6]).unwrap();
+    ///
+    /// let mut out = Vec::new();
+    /// buf.write_delimited(&mut out, ',').unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(out).unwrap(), "1,2,3\n4,5,6\n");
+    /// ```
+    pub fn write_delimited<W: Write>(&self, mut writer: W, delimiter: char) -> Result<(), anyhow::Error>
+    where
+        T: Display,
+    {
+        write_rows(&mut writer, self.as_slice(), 0, &self.size, &self.stride, delimiter)
+    }
+}
+
+impl<T, S: Storage<T>> XDBuf<T, 3, S> {
+    /// Write the buffer as delimited text, one block per z-slice.
+    ///
+    /// Each slice is written in the same format as [`XDBuf::write_delimited`], and slices are separated by an empty line.
+    /// Slices are written one at a time, so no intermediate buffer is built.
+    ///
+    /// バッファをz方向のスライスごとのブロックとして、区切り文字で区切られたテキストに書き出します。
+    ///
+    /// 各スライスは[`XDBuf::write_delimited`]と同じ形式で書き出され、スライス間は空行で区切られます。
+    /// スライスは1枚ずつ書き出されるため、中間バッファは生成されません。
+    ///
+    /// # Errors
+    ///
+    /// * Error if writing to `writer` fails.
+    ///
+    /// * `writer`への書き込みに失敗した場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 3>::new_with_vec([2, 2, 2], (1..=8).collect()).unwrap();
+    ///
+    /// let mut out = Vec::new();
+    /// buf.write_delimited_slices(&mut out, ',').unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(out).unwrap(), "1,2\n3,4\n\n5,6\n7,8\n");
+    /// ```
+    pub fn write_delimited_slices<W: Write>(&self, mut writer: W, delimiter: char) -> Result<(), anyhow::Error>
+    where
+        T: Display,
+    {
+        let size = [self.size[0], self.size[1]];
+        let stride = [self.stride[0], self.stride[1]];
+
+        for z in 0..self.size[2] {
+            if z > 0 {
+                writeln!(writer)?;
+            }
+
+            write_rows(&mut writer, self.as_slice(), z * self.stride[2], &size, &stride, delimiter)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the 2-dimensional slice of `values` starting at `offset` as lines of fields separated by `delimiter`.
+///
+/// `values`の`offset`から始まる2次元のスライスを、`delimiter`で区切ったフィールドの行として書き出します。
+fn write_rows<T: Display, W: Write>(
+    writer: &mut W,
+    values: &[T],
+    offset: usize,
+    size: &[usize; 2],
+    stride: &[usize; 2],
+    delimiter: char,
+) -> Result<(), anyhow::Error> {
+    for y in 0..size[1] {
+        for x in 0..size[0] {
+            if x > 0 {
+                write!(writer, "{}", delimiter)?;
+            }
+            write!(writer, "{}", values[offset + x * stride[0] + y * stride[1]])?;
+        }
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Layout;
+
+    #[test]
+    fn from_delimited_round_trips() {
+        let text = "0.5\t1.5\n2.5\t3.5\n4.5\t5.5\n";
+        let buf = XDBuf::<f32, 2>::from_delimited(text.as_bytes(), '\t').unwrap();
+        assert_eq!(buf.size(), [2, 3]);
+
+        let mut out = Vec::new();
+        buf.write_delimited(&mut out, '\t').unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), text);
+    }
+
+    #[test]
+    fn from_delimited_skips_empty_lines_and_trims_fields() {
+        let text = "1, 2\r\n\n3 , 4\n\n";
+        let buf = XDBuf::<i32, 2>::from_delimited(text.as_bytes(), ',').unwrap();
+        assert_eq!(buf.size(), [2, 2]);
+        assert_eq!(buf.buf, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn from_delimited_merges_runs_of_whitespace() {
+        let text = "  1  2 3
+ 10 20	30
+";
+        let buf = XDBuf::<i32, 2>::from_delimited(text.as_bytes(), ' ').unwrap();
+        assert_eq!(buf.size(), [3, 2]);
+        assert_eq!(buf.buf, vec![1, 2, 3, 10, 20, 30]);
+    }
+
+    #[test]
+    fn from_delimited_reports_line_number() {
+        let text = "1,2\n\n3,4\n5\n";
+        let err = XDBuf::<i32, 2>::from_delimited(text.as_bytes(), ',').unwrap_err();
+        assert!(err.to_string().contains("line 4"));
+
+        let text = "1,2\nx,4\n";
+        let err = XDBuf::<i32, 2>::from_delimited(text.as_bytes(), ',').unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn write_delimited_follows_layout() {
+        let buf = XDBuf::<i32, 3>::new_with_vec([2, 2, 2], (1..=8).collect()).unwrap();
+        let relayout = buf.relayout(Layout::LastMajor);
+
+        let mut out = Vec::new();
+        relayout.write_delimited_slices(&mut out, ',').unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1,2\n3,4\n\n5,6\n7,8\n");
+    }
+
+    #[test]
+    fn write_delimited_works_with_borrowed_storage() {
+        let data = [1, 2, 3, 4];
+        let buf = XDBuf::<i32, 2, _>::from_storage([2, 2], &data[..]).unwrap();
+
+        let mut out = Vec::new();
+        buf.write_delimited(&mut out, ' ').unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1 2\n3 4\n");
+    }
+
+    #[test]
+    fn from_delimited_rejects_empty_input() {
+        assert!(XDBuf::<i32, 2>::from_delimited("".as_bytes(), ',').is_err());
+    }
+}
@@ -0,0 +1,936 @@
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use anyhow::anyhow;
+use smallvec::SmallVec;
+
+use crate::layout::Layout;
+use crate::XDBuf;
+
+/// Index in array notation, or the size of each dimension, of an `XDBufDyn`
+///
+/// Up to 4 dimensions are held without heap allocation.
+///
+/// `XDBufDyn`の配列表記のインデックス、または各次元のサイズです。
+///
+/// 4次元まではヒープに割り当てずに保持されます。
+pub type DynIndex = SmallVec<[usize; 4]>;
+
+/// Structure representing an n-dimensional buffer whose number of dimensions is determined at runtime
+///
+/// Reusing a single instance reduces memory allocation, including when the number of dimensions changes.
+///
+/// 次元数が実行時に決まるn次元のバッファを表す構造体です。
+///
+/// 次元数が変わる場合も含め、単一のインスタンスを再利用することでメモリの割り当てを削減できます。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::{XDBuf, XDBufDyn};
+///
+/// // ファイルのヘッダーから読み込んだ形状
+/// let header = vec![3, 4, 5];
+/// let buf = XDBufDyn::<f32>::new(&header, 0.0).unwrap();
+/// assert_eq!(buf.dim(), 3);
+///
+/// let fixed = XDBuf::<f32, 3>::try_from(buf).unwrap();
+/// assert_eq!(fixed.size(), [3, 4, 5]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct XDBufDyn<T> {
+    buf: Vec<T>,
+    size: DynIndex,
+    stride: DynIndex,
+    layout: Layout,
+}
+
+impl<T> XDBufDyn<T> {
+    /// Generate a new `XDBufDyn`.
+    ///
+    /// Allocates the specified amount of elements for each dimension and fills them with initial values.
+    ///
+    /// 新しい`XDBufDyn`を生成します。
+    ///
+    /// それぞれの次元について指定した分の要素を確保し、初期値で埋めます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `size` is empty or contains 0.
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `size`が空、または0を含む場合エラーになります。
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let buf = XDBufDyn::<i32>::new(&[3, 4, 5], 0).unwrap();
+    /// assert_eq!(buf.len(), 60);
+    /// ```
+    pub fn new(size: &[usize], initial_value: T) -> Result<Self, anyhow::Error>
+    where
+        T: Clone,
+    {
+        Self::new_with_layout(size, initial_value, Layout::default())
+    }
+
+    /// Generate a new `XDBufDyn` with the specified memory `layout`.
+    ///
+    /// 指定したメモリ配置`layout`で新しい`XDBufDyn`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `size` is empty or contains 0.
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `size`が空、または0を含む場合エラーになります。
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Layout, XDBufDyn};
+    ///
+    /// let buf = XDBufDyn::<i32>::new_with_layout(&[3, 4, 5], 0, Layout::LastMajor).unwrap();
+    /// assert_eq!(buf.stride(), &[20, 5, 1]);
+    /// ```
+    pub fn new_with_layout(size: &[usize], initial_value: T, layout: Layout) -> Result<Self, anyhow::Error>
+    where
+        T: Clone,
+    {
+        let total_size = Self::calc_total_size(size)?;
+
+        Ok(Self {
+            buf: vec![initial_value; total_size],
+            size: size.into(),
+            stride: Self::calc_dim_stride_with_layout(size, layout)?,
+            layout,
+        })
+    }
+
+    /// Generate a new `XDBufDyn` from `Vec<T>`.
+    ///
+    /// `Vec<T>`から新しい`XDBufDyn`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `size` is empty or contains 0.
+    /// * Error if the length of `initial_vec` does not match the total product of `size`.
+    ///
+    /// * `size`が空、または0を含む場合エラーになります。
+    /// * `initial_vec`の長さが`size`の総積と一致しない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let buf = XDBufDyn::new_with_vec(&[3, 2], (1..=6).collect()).unwrap();
+    /// assert_eq!(buf.get_m(&[2, 1]), Some(&6));
+    /// ```
+    pub fn new_with_vec(size: &[usize], initial_vec: Vec<T>) -> Result<Self, anyhow::Error> {
+        if initial_vec.len() != Self::calc_total_size(size)? {
+            return Err(anyhow!("length of initial_vec does not match size"));
+        }
+
+        Ok(Self {
+            buf: initial_vec,
+            size: size.into(),
+            stride: Self::calc_dim_stride_with_layout(size, Layout::default())?,
+            layout: Layout::default(),
+        })
+    }
+
+    /// Initialize the buffer.
+    ///
+    /// The number of dimensions may differ from the current one.
+    /// The internally allocated capacity is not affected in the shrink direction.
+    ///
+    /// バッファを初期化します。
+    ///
+    /// 次元数は現在のものと異なっていても構いません。
+    /// 内部に割り当てられた容量には縮小方向への影響を与えません。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `size` is empty or contains 0.
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `size`が空、または0を含む場合エラーになります。
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let mut buf = XDBufDyn::<i32>::new(&[3, 4, 5], 0).unwrap();
+    ///
+    /// buf.init(&[2, 3], 1).unwrap();
+    /// assert_eq!(buf.dim(), 2);
+    /// assert_eq!(buf.len(), 6);
+    /// ```
+    pub fn init(&mut self, size: &[usize], initial_value: T) -> Result<(), anyhow::Error>
+    where
+        T: Clone,
+    {
+        let total_size = Self::calc_total_size(size)?;
+        self.stride = Self::calc_dim_stride_with_layout(size, self.layout)?;
+        self.size = size.into();
+
+        self.buf.clear();
+        self.buf.resize(total_size, initial_value);
+
+        Ok(())
+    }
+
+    /// Initialize the buffer from `Vec<T>`.
+    ///
+    /// `Vec<T>`からバッファを初期化します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `size` is empty or contains 0.
+    /// * Error if the length of `initial_vec` does not match the total product of `size`.
+    ///
+    /// * `size`が空、または0を含む場合エラーになります。
+    /// * `initial_vec`の長さが`size`の総積と一致しない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let mut buf = XDBufDyn::<i32>::new(&[3, 4, 5], 0).unwrap();
+    ///
+    /// buf.init_with_vec(&[6], vec![1; 6]).unwrap();
+    /// assert_eq!(buf.size(), &[6]);
+    /// ```
+    pub fn init_with_vec(&mut self, size: &[usize], mut initial_vec: Vec<T>) -> Result<(), anyhow::Error> {
+        if initial_vec.len() != Self::calc_total_size(size)? {
+            return Err(anyhow!("length of initial_vec does not match size"));
+        }
+
+        self.stride = Self::calc_dim_stride_with_layout(size, self.layout)?;
+        self.size = size.into();
+
+        self.buf.clear();
+        self.buf.append(&mut initial_vec);
+
+        Ok(())
+    }
+
+    /// Computes the number of elements in a multidimensional array.
+    ///
+    /// 多次元配列の要素数を計算します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `size` is empty or contains 0.
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `size`が空、または0を含む場合エラーになります。
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// assert_eq!(XDBufDyn::<i32>::calc_total_size(&[3, 4, 5]).unwrap(), 60);
+    /// assert!(XDBufDyn::<i32>::calc_total_size(&[]).is_err());
+    /// ```
+    pub fn calc_total_size(size: &[usize]) -> Result<usize, anyhow::Error> {
+        if size.is_empty() || size.contains(&0) {
+            return Err(anyhow!("size is out of range"));
+        }
+
+        size.iter().try_fold(1_usize, |acc, &v| {
+            acc.checked_mul(v).ok_or(
+                anyhow!("size is out of range")
+            )
+        })
+    }
+
+    /// Calculates the number of elements each dimension of a multidimensional array has, for the given `layout`.
+    ///
+    /// 指定された`layout`について、多次元配列の各次元が持つ要素数を計算します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Layout, XDBufDyn};
+    ///
+    /// let stride = XDBufDyn::<i32>::calc_dim_stride_with_layout(&[3, 4, 5], Layout::FirstMajor).unwrap();
+    /// assert_eq!(stride.as_slice(), &[1, 3, 12]);
+    /// ```
+    pub fn calc_dim_stride_with_layout(size: &[usize], layout: Layout) -> Result<DynIndex, anyhow::Error> {
+        let mut stride: DynIndex = SmallVec::from_elem(1, size.len());
+        let mut acc = 1_usize;
+
+        for n in 0..size.len() {
+            let i = match layout {
+                Layout::FirstMajor => n,
+                Layout::LastMajor => size.len() - 1 - n,
+            };
+            stride[i] = acc;
+
+            // 最も遅い軸のサイズはストライドに影響しない
+            if n + 1 < size.len() {
+                acc = acc.checked_mul(size[i]).ok_or(
+                    anyhow!("size is out of range")
+                )?;
+            }
+        }
+
+        Ok(stride)
+    }
+
+    /// Convert an index in array notation to a scalar index.
+    ///
+    /// 配列表記のインデックスをスカラーのインデックスに変換します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `index` differs from the number of dimensions.
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`の長さが次元数と異なる場合エラーになります。
+    /// * `index`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let buf = XDBufDyn::<i32>::new(&[3, 4, 5], 0).unwrap();
+    ///
+    /// assert_eq!(buf.to_scalar_index(&[1, 2, 3]).unwrap(), 3*4*3 + 3*2 + 1);
+    /// assert!(buf.to_scalar_index(&[1, 2]).is_err());
+    /// ```
+    pub fn to_scalar_index(&self, index: &[usize]) -> Result<usize, anyhow::Error> {
+        if index.len() != self.dim() {
+            return Err(anyhow!("index has {} dimensions, but the buffer has {}", index.len(), self.dim()));
+        }
+
+        if index.iter().zip(&self.size).any(|(&i, &s)| i >= s) {
+            return Err(anyhow!("index is out of range"));
+        }
+
+        Ok(index.iter().zip(&self.stride).map(|(&i, &s)| i * s).sum())
+    }
+
+    /// Convert scalar index to array notation.
+    ///
+    /// スカラーのインデックスを配列表記に変換します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let buf = XDBufDyn::<i32>::new(&[3, 4, 5], 0).unwrap();
+    ///
+    /// let index = buf.to_mul_dim_index(1 + 2*3 + 3*4*3);
+    /// assert_eq!(index.as_slice(), &[1, 2, 3]);
+    /// ```
+    pub fn to_mul_dim_index(&self, mut scalar: usize) -> DynIndex {
+        let mut index: DynIndex = SmallVec::from_elem(0, self.dim());
+
+        for n in (0..self.dim()).rev() {
+            let i = match self.layout {
+                Layout::FirstMajor => n,
+                Layout::LastMajor => self.dim() - 1 - n,
+            };
+            index[i] = scalar / self.stride[i];
+            scalar %= self.stride[i];
+        }
+
+        index
+    }
+
+    /// Get a reference to the element specified by `index`.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// `index`で指定された要素の参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.buf.get(index)
+    }
+
+    /// Get a variable reference to the element specified by `index`.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// `index`で指定された要素の可変参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.buf.get_mut(index)
+    }
+
+    /// Set `value` to the element specified by `index`.
+    ///
+    /// `index`で指定された要素に`value`を設定します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    pub fn set(&mut self, index: usize, value: T) -> Result<(), anyhow::Error> {
+        *self.get_mut(index).ok_or(anyhow!("index is out of range"))? = value;
+
+        Ok(())
+    }
+
+    /// Get a reference to the element specified by `index` in array notation.
+    ///
+    /// Returns `None` if `index` is out of range or its length differs from the number of dimensions.
+    ///
+    /// 配列表記の`index`で指定された要素の参照を取得します。
+    ///
+    /// `index`が範囲外、またはその長さが次元数と異なる場合は`None`を返します。
+    pub fn get_m(&self, index: &[usize]) -> Option<&T> {
+        self.to_scalar_index(index).ok().and_then(|i| self.get(i))
+    }
+
+    /// Get a variable reference to the element specified by `index` in array notation.
+    ///
+    /// Returns `None` if `index` is out of range or its length differs from the number of dimensions.
+    ///
+    /// 配列表記の`index`で指定された要素の可変参照を取得します。
+    ///
+    /// `index`が範囲外、またはその長さが次元数と異なる場合は`None`を返します。
+    pub fn get_mut_m(&mut self, index: &[usize]) -> Option<&mut T> {
+        self.to_scalar_index(index).ok().and_then(|i| self.get_mut(i))
+    }
+
+    /// Set `value` to the element specified by `index` in array notation.
+    ///
+    /// 配列表記の`index`で指定された要素に`value`を設定します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `index` differs from the number of dimensions.
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`の長さが次元数と異なる場合エラーになります。
+    /// * `index`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let mut buf = XDBufDyn::<i32>::new(&[3, 3], 0).unwrap();
+    ///
+    /// buf.set_m(&[1, 2], 5).unwrap();
+    /// assert_eq!(buf.get(7), Some(&5));
+    /// ```
+    pub fn set_m(&mut self, index: &[usize], value: T) -> Result<(), anyhow::Error> {
+        let scalar = self.to_scalar_index(index)?;
+
+        self.set(scalar, value)
+    }
+
+    /// Returns an iterator over the elements in memory order.
+    ///
+    /// 要素をメモリ上の順序で走査するイテレータを返します。
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.buf.iter()
+    }
+
+    /// Returns an iterator that allows modifying each element in memory order.
+    ///
+    /// 各要素をメモリ上の順序で変更できるイテレータを返します。
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.buf.iter_mut()
+    }
+
+    /// Generates a `WalkerDyn` with the specified `index` as its initial position.
+    ///
+    /// 指定された`index`を初期位置として`WalkerDyn`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `index` differs from the number of dimensions.
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`の長さが次元数と異なる場合エラーになります。
+    /// * `index`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let buf = XDBufDyn::new_with_vec(&[3, 3], (1..=9).collect()).unwrap();
+    /// let mut walker = buf.walker_from_m(&[1, 1]).unwrap();
+    ///
+    /// walker.as_(&[1, -1]).unwrap();
+    /// assert_eq!(buf.get(walker.index_s()), Some(&3));
+    /// ```
+    pub fn walker_from_m(&self, index: &[usize]) -> Result<WalkerDyn<'_, T>, anyhow::Error> {
+        let scalar = self.to_scalar_index(index)?;
+
+        self.walker_from_s(scalar)
+    }
+
+    /// Generates a `WalkerDyn` with the specified `index` as its initial position.
+    ///
+    /// 指定された`index`を初期位置として`WalkerDyn`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `scalar_index` is out of range.
+    ///
+    /// * `scalar_index`が範囲外の場合エラーになります。
+    pub fn walker_from_s(&self, scalar_index: usize) -> Result<WalkerDyn<'_, T>, anyhow::Error> {
+        if scalar_index >= self.len() {
+            return Err(anyhow!("index is out of range"));
+        }
+
+        Ok(WalkerDyn {
+            buf_into: self,
+            current_index: scalar_index,
+        })
+    }
+
+    /// Returns the number of dimensions of the buffer.
+    ///
+    /// バッファの次元数を返します。
+    pub fn dim(&self) -> usize {
+        self.size.len()
+    }
+
+    /// Returns the number of elements in the buffer.
+    ///
+    /// バッファの要素数を返します。
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if the buffer contains no elements.
+    ///
+    /// バッファが要素を持たない場合に`true`を返します。
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Returns the range of scalar indices of the buffer.
+    ///
+    /// バッファのスカラーのインデックスの範囲を返します。
+    pub fn idx_range(&self) -> Range<usize> {
+        0..self.len()
+    }
+
+    /// Returns the size of each dimension of the buffer.
+    ///
+    /// バッファの各次元のサイズを返します。
+    pub fn size(&self) -> &[usize] {
+        &self.size
+    }
+
+    /// Returns the number of elements each dimension of the buffer has.
+    ///
+    /// バッファの各次元が持つ要素数を返します。
+    pub fn stride(&self) -> &[usize] {
+        &self.stride
+    }
+
+    /// Returns the memory layout of the buffer.
+    ///
+    /// バッファのメモリ配置を返します。
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns the elements of the buffer as a slice in memory order.
+    ///
+    /// バッファの要素をメモリ上の順序のスライスとして返します。
+    pub fn as_slice(&self) -> &[T] {
+        &self.buf
+    }
+
+    /// Returns the elements of the buffer as a mutable slice in memory order.
+    ///
+    /// バッファの要素をメモリ上の順序の可変なスライスとして返します。
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.buf
+    }
+
+    /// Reduce buffer capacity as much as possible.
+    ///
+    /// バッファが確保しているメモリ容量をできるだけ縮小します。
+    pub fn shrink_to_fit(&mut self) {
+        self.buf.shrink_to_fit();
+    }
+}
+
+/// Converts a buffer whose number of dimensions matches `D`. The elements are not copied.
+///
+/// 次元数が`D`と一致するバッファを変換します。要素は複製されません。
+///
+/// # Errors
+///
+/// * Error if the number of dimensions differs from `D`.
+///
+/// * 次元数が`D`と異なる場合エラーになります。
+impl<T, const D: usize> TryFrom<XDBufDyn<T>> for XDBuf<T, D> {
+    type Error = anyhow::Error;
+
+    fn try_from(value: XDBufDyn<T>) -> Result<Self, Self::Error> {
+        if value.dim() != D {
+            return Err(anyhow!("buffer has {} dimensions, but {} were expected", value.dim(), D));
+        }
+
+        let mut size = [0; D];
+        let mut stride = [0; D];
+        size.copy_from_slice(&value.size);
+        stride.copy_from_slice(&value.stride);
+
+        Ok(XDBuf {
+            buf: value.buf,
+            size,
+            stride,
+            layout: value.layout,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Converts a buffer with at least one dimension. The elements are not copied.
+///
+/// 1つ以上の次元を持つバッファを変換します。要素は複製されません。
+///
+/// # Errors
+///
+/// * Error if `D` is 0.
+///
+/// * `D`が0の場合エラーになります。
+impl<T, const D: usize> TryFrom<XDBuf<T, D>> for XDBufDyn<T> {
+    type Error = anyhow::Error;
+
+    fn try_from(value: XDBuf<T, D>) -> Result<Self, Self::Error> {
+        if D == 0 {
+            return Err(anyhow!("buffer has 0 dimensions, but at least 1 is required"));
+        }
+
+        Ok(Self {
+            buf: value.buf,
+            size: SmallVec::from_slice(&value.size),
+            stride: SmallVec::from_slice(&value.stride),
+            layout: value.layout,
+        })
+    }
+}
+
+/// Structure for index operations on an `XDBufDyn`
+///
+/// Steps are given as slices whose length is the number of dimensions of the buffer.
+///
+/// `XDBufDyn`におけるインデックス操作を行うための構造体
+///
+/// 移動量はバッファの次元数と同じ長さのスライスで指定します。
+#[derive(Debug)]
+pub struct WalkerDyn<'a, T> {
+    buf_into: &'a XDBufDyn<T>,
+    current_index: usize,
+}
+
+impl<T> Clone for WalkerDyn<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for WalkerDyn<'_, T> {}
+
+impl<T> WalkerDyn<'_, T> {
+    /// Returns the current index.
+    ///
+    /// 現在のインデックスを返します。
+    pub fn index_s(&self) -> usize {
+        self.current_index
+    }
+
+    /// Returns the current index.
+    ///
+    /// 現在のインデックスを返します。
+    pub fn index_m(&self) -> DynIndex {
+        self.buf_into.to_mul_dim_index(self.current_index)
+    }
+
+    /// Returns the current index plus `step`.
+    ///
+    /// 現在のインデックスから`step`を加算したインデックスを返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `step` differs from the number of dimensions.
+    /// * Error if the destination index is out of range.
+    ///
+    /// * `step`の長さが次元数と異なる場合エラーになります。
+    /// * 移動先のインデックスが範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let buf = XDBufDyn::new_with_vec(&[3, 3], (1..=9).collect()).unwrap();
+    /// let walker = buf.walker_from_m(&[1, 1]).unwrap();
+    ///
+    /// assert_eq!(walker.index_(&[1, 0]).unwrap(), 5);
+    /// assert_eq!(walker.index_(&[0, 1]).unwrap(), 7);
+    /// assert!(walker.index_(&[2, 0]).is_err());
+    /// ```
+    pub fn index_(&self, step: &[isize]) -> Result<usize, anyhow::Error> {
+        if step.len() != self.buf_into.dim() {
+            return Err(anyhow!("step has {} dimensions, but the buffer has {}", step.len(), self.buf_into.dim()));
+        }
+
+        let mut index = self.index_m();
+
+        index.iter_mut().zip(step).try_for_each(|(current_index, &step)| {
+            *current_index = current_index.checked_add_signed(step).ok_or(
+                anyhow!("Index out of range")
+            )?;
+
+            Ok::<_, anyhow::Error>(())
+        })?;
+
+        self.buf_into.to_scalar_index(&index)
+    }
+
+    /// Moves to the current index plus `step`.
+    ///
+    /// 現在のインデックスから`step`を加算したインデックスに移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `step` differs from the number of dimensions.
+    /// * Error if the destination index is out of range.
+    ///
+    /// * `step`の長さが次元数と異なる場合エラーになります。
+    /// * 移動先のインデックスが範囲外の場合エラーになります。
+    pub fn as_(&mut self, step: &[isize]) -> Result<&mut Self, anyhow::Error> {
+        self.current_index = self.index_(step)?;
+        Ok(self)
+    }
+
+    /// Moves to the current index plus `step`.
+    ///
+    /// 現在のインデックスから`step`を加算したインデックスに移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `step` differs from the number of dimensions.
+    /// * Error if the destination index is out of range.
+    ///
+    /// * `step`の長さが次元数と異なる場合エラーになります。
+    /// * 移動先のインデックスが範囲外の場合エラーになります。
+    pub fn into_(mut self, step: &[isize]) -> Result<Self, anyhow::Error> {
+        self.as_(step)?;
+        Ok(self)
+    }
+
+    /// Returns the next index.
+    ///
+    /// 次のインデックスを返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination index is out of range.
+    ///
+    /// * 次のインデックスが範囲外の場合エラーになります。
+    pub fn next_index(&self) -> Result<usize, anyhow::Error> {
+        let next_index = self.current_index.checked_add(1).ok_or(
+            anyhow!("Index out of range")
+        )?;
+
+        if next_index >= self.buf_into.len() {
+            return Err(anyhow!("Index out of range"));
+        }
+
+        Ok(next_index)
+    }
+
+    /// Moves to the next index.
+    ///
+    /// 次のインデックスに移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination index is out of range.
+    ///
+    /// * 次のインデックスが範囲外の場合エラーになります。
+    pub fn as_next(&mut self) -> Result<&mut Self, anyhow::Error> {
+        self.current_index = self.next_index()?;
+        Ok(self)
+    }
+
+    /// Moves to the next index.
+    ///
+    /// 次のインデックスに移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination index is out of range.
+    ///
+    /// * 次のインデックスが範囲外の場合エラーになります。
+    pub fn into_next(mut self) -> Result<Self, anyhow::Error> {
+        self.as_next()?;
+        Ok(self)
+    }
+
+    /// Returns the previous index.
+    ///
+    /// 前のインデックスを返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination index is out of range.
+    ///
+    /// * 前のインデックスが範囲外の場合エラーになります。
+    pub fn prev_index(&self) -> Result<usize, anyhow::Error> {
+        self.current_index.checked_sub(1).ok_or(
+            anyhow!("Index out of range")
+        )
+    }
+
+    /// Moves to the previous index.
+    ///
+    /// 前のインデックスに移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination index is out of range.
+    ///
+    /// * 前のインデックスが範囲外の場合エラーになります。
+    pub fn as_prev(&mut self) -> Result<&mut Self, anyhow::Error> {
+        self.current_index = self.prev_index()?;
+        Ok(self)
+    }
+
+    /// Moves to the previous index.
+    ///
+    /// 前のインデックスに移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the destination index is out of range.
+    ///
+    /// * 前のインデックスが範囲外の場合エラーになります。
+    pub fn into_prev(mut self) -> Result<Self, anyhow::Error> {
+        self.as_prev()?;
+        Ok(self)
+    }
+
+    /// Traverses elements after the current index and returns the first index that satisfies the condition.
+    ///
+    /// 現在のインデックス以降の要素を走査し、条件を満たす最初のインデックスを返します。
+    ///
+    /// # Errors
+    ///
+    /// * An error will occur if no element is found that satisfies the condition up to the last element.
+    ///
+    /// * 最後の要素まで条件を満たす要素が見つからない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufDyn;
+    ///
+    /// let buf = XDBufDyn::new_with_vec(&[3, 3], (1..=9).collect()).unwrap();
+    /// let walker = buf.walker_from_s(0).unwrap();
+    ///
+    /// assert_eq!(walker.index_until(|&x, _i| x == 5).unwrap(), 4);
+    /// assert!(walker.index_until(|&x, _i| x < 0).is_err());
+    /// ```
+    pub fn index_until(&self, f: impl Fn(&T, usize) -> bool) -> Result<usize, anyhow::Error> {
+        (self.current_index..self.buf_into.len())
+            .find(|&i| f(&self.buf_into.as_slice()[i], i))
+            .ok_or(anyhow!("No element satisfying the function exists"))
+    }
+
+    /// Traverses elements after the current index and moves to the first index that satisfies the condition.
+    ///
+    /// 現在のインデックス以降の要素を走査し、条件を満たす最初のインデックスに移動します。
+    ///
+    /// # Errors
+    ///
+    /// * An error will occur if no element is found that satisfies the condition up to the last element.
+    ///
+    /// * 最後の要素まで条件を満たす要素が見つからない場合エラーになります。
+    pub fn as_until(&mut self, f: impl Fn(&T, usize) -> bool) -> Result<&mut Self, anyhow::Error> {
+        self.current_index = self.index_until(f)?;
+        Ok(self)
+    }
+
+    /// Traverses elements after the current index and moves to the first index that satisfies the condition.
+    ///
+    /// 現在のインデックス以降の要素を走査し、条件を満たす最初のインデックスに移動します。
+    ///
+    /// # Errors
+    ///
+    /// * An error will occur if no element is found that satisfies the condition up to the last element.
+    ///
+    /// * 最後の要素まで条件を満たす要素が見つからない場合エラーになります。
+    pub fn into_until(mut self, f: impl Fn(&T, usize) -> bool) -> Result<Self, anyhow::Error> {
+        self.as_until(f)?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn index_conversion_matches_xdbuf() {
+        for layout in [Layout::FirstMajor, Layout::LastMajor] {
+            let fixed = XDBuf::<usize, 3>::new_with_layout([3, 4, 5], 0, layout).unwrap();
+            let dynamic = XDBufDyn::<usize>::new_with_layout(&[3, 4, 5], 0, layout).unwrap();
+
+            assert_eq!(dynamic.stride(), fixed.stride());
+            for i in fixed.idx_range() {
+                assert_eq!(dynamic.to_mul_dim_index(i).as_slice(), fixed.to_mul_dim_index(i));
+                assert_eq!(dynamic.to_scalar_index(&fixed.to_mul_dim_index(i)).unwrap(), i);
+            }
+        }
+    }
+
+    #[test]
+    fn conversions_round_trip_without_copying() {
+        let fixed = XDBuf::<i32, 2>::new_with_vec([3, 2], (1..=6).collect()).unwrap();
+        let ptr = fixed.as_slice().as_ptr();
+
+        let dynamic = XDBufDyn::try_from(fixed).unwrap();
+        assert_eq!(dynamic.size(), &[3, 2]);
+        assert!(XDBuf::<i32, 3>::try_from(dynamic.clone()).is_err());
+
+        let back = XDBuf::<i32, 2>::try_from(dynamic).unwrap();
+        assert_eq!(back.as_slice().as_ptr(), ptr);
+        assert_eq!(back.get_m([2, 1]), Some(&6));
+
+        let scalar = XDBuf::<i32, 0>::new([], 7).unwrap();
+        assert!(XDBufDyn::try_from(scalar).is_err());
+    }
+
+    #[test]
+    fn walker_rejects_mismatched_step() {
+        let buf = XDBufDyn::<u8>::new(&[2, 2, 2, 2, 2], 0).unwrap();
+        let walker = buf.walker_from_m(&[1, 1, 1, 1, 1]).unwrap();
+
+        assert!(walker.index_(&[0, 0]).is_err());
+        assert_eq!(walker.index_(&[-1, -1, -1, -1, -1]).unwrap(), 0);
+        assert_eq!(walker.into_prev().unwrap().index_m().as_slice(), &[0, 1, 1, 1, 1]);
+    }
+}
@@ -0,0 +1,383 @@
+use std::ops::{Add, Mul, Sub};
+
+use anyhow::anyhow;
+use num_traits::{Float, Zero};
+
+use crate::storage::Storage;
+use crate::XDBuf;
+
+/// Summed-area table of an `XDBuf` answering box sums in constant time
+///
+/// The table keeps one more element than the source along each axis, so that each box sum is
+/// obtained from `2^D` elements by inclusion-exclusion. Optionally, a second table of squared values
+/// is kept to compute box variances.
+///
+/// The sums are accumulated in `A`, which can be wider than the element type of the source,
+/// such as `u64` for an image of `u8`, so that the sums do not overflow.
+///
+/// 直方体の和を定数時間で求める`XDBuf`の累積和テーブルです。
+///
+/// テーブルは各軸について元のバッファより1つ多くの要素を保持し、直方体の和は包除原理により
+/// `2^D`個の要素から求められます。直方体の分散を計算するために、二乗した値の2つ目のテーブルを保持することもできます。
+///
+/// 和がオーバーフローしないように、`u8`の画像に対する`u64`など、元の要素の型より広い型`A`で累積されます。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::{IntegralXDBuf, XDBuf};
+///
+/// let image = XDBuf::<u8, 2>::new([100, 100], 255).unwrap();
+/// let integral = IntegralXDBuf::<u64, 2>::new(&image).unwrap();
+///
+/// assert_eq!(integral.box_sum([10, 20], [19, 49]).unwrap(), 300 * 255);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IntegralXDBuf<A, const D: usize> {
+    sums: XDBuf<A, D>,
+    squares: Option<XDBuf<A, D>>,
+}
+
+impl<A, const D: usize> IntegralXDBuf<A, D>
+where
+    A: Copy + Zero + Add<Output = A> + Sub<Output = A> + Mul<Output = A>,
+{
+    /// Generate a new `IntegralXDBuf` from `src`.
+    ///
+    /// `src`から新しい`IntegralXDBuf`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of the size of the table exceeds the range of `usize`.
+    ///
+    /// * テーブルのサイズの総積が`usize`の範囲を超える場合エラーになります。
+    pub fn new<T: Copy + Into<A>, S: Storage<T>>(src: &XDBuf<T, D, S>) -> Result<Self, anyhow::Error> {
+        let mut integral = Self {
+            sums: XDBuf::new([1; D], A::zero())?,
+            squares: None,
+        };
+        integral.rebuild(src)?;
+
+        Ok(integral)
+    }
+
+    /// Generate a new `IntegralXDBuf` from `src` that also keeps the sums of squared values.
+    ///
+    /// 二乗した値の和も保持する新しい`IntegralXDBuf`を`src`から生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of the size of the table exceeds the range of `usize`.
+    ///
+    /// * テーブルのサイズの総積が`usize`の範囲を超える場合エラーになります。
+    pub fn new_with_squares<T: Copy + Into<A>, S: Storage<T>>(src: &XDBuf<T, D, S>) -> Result<Self, anyhow::Error> {
+        let mut integral = Self {
+            sums: XDBuf::new([1; D], A::zero())?,
+            squares: Some(XDBuf::new([1; D], A::zero())?),
+        };
+        integral.rebuild(src)?;
+
+        Ok(integral)
+    }
+
+    /// Rebuilds the tables from `src`, such as after it was reinitialized.
+    ///
+    /// The tables are reinitialized with `init`, so their allocated capacity is reused.
+    ///
+    /// 再初期化された後などに、`src`からテーブルを再構築します。
+    ///
+    /// テーブルは`init`で再初期化されるため、割り当て済みの容量が再利用されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of the size of the table exceeds the range of `usize`.
+    ///
+    /// * テーブルのサイズの総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{IntegralXDBuf, XDBuf};
+    ///
+    /// let mut buf = XDBuf::<i64, 3>::new([8, 8, 8], 1).unwrap();
+    /// let mut integral = IntegralXDBuf::<i64, 3>::new(&buf).unwrap();
+    ///
+    /// buf.init([4, 4, 4], 2).unwrap();
+    /// integral.rebuild(&buf).unwrap();
+    ///
+    /// assert_eq!(integral.size(), [4, 4, 4]);
+    /// assert_eq!(integral.box_sum([0, 0, 0], [3, 3, 3]).unwrap(), 128);
+    /// ```
+    pub fn rebuild<T: Copy + Into<A>, S: Storage<T>>(&mut self, src: &XDBuf<T, D, S>) -> Result<(), anyhow::Error> {
+        accumulate(&mut self.sums, src, |v| v.into())?;
+
+        if let Some(squares) = &mut self.squares {
+            accumulate(squares, src, |v| {
+                let v = v.into();
+                v * v
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the size of the source buffer.
+    ///
+    /// 元のバッファのサイズを返します。
+    pub fn size(&self) -> [usize; D] {
+        self.sums.size().map(|s| s - 1)
+    }
+
+    /// Returns whether the sums of squared values are kept.
+    ///
+    /// 二乗した値の和を保持しているかを返します。
+    pub fn has_squares(&self) -> bool {
+        self.squares.is_some()
+    }
+
+    /// Returns the sum of the elements in the box from `min` to `max`, both inclusive.
+    ///
+    /// 両端を含む`min`から`max`までの直方体の要素の和を返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `max` is out of range.
+    /// * Error if `min` exceeds `max` along an axis.
+    ///
+    /// * `max`が範囲外の場合エラーになります。
+    /// * いずれかの軸で`min`が`max`を超える場合エラーになります。
+    pub fn box_sum(&self, min: [usize; D], max: [usize; D]) -> Result<A, anyhow::Error> {
+        self.validate_box(&min, &max)?;
+
+        Ok(box_sum(&self.sums, &min, &max))
+    }
+
+    /// Returns the sum of the squared elements in the box from `min` to `max`, both inclusive.
+    ///
+    /// 両端を含む`min`から`max`までの直方体の二乗した要素の和を返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the sums of squared values are not kept.
+    /// * Error if `max` is out of range.
+    /// * Error if `min` exceeds `max` along an axis.
+    ///
+    /// * 二乗した値の和を保持していない場合エラーになります。
+    /// * `max`が範囲外の場合エラーになります。
+    /// * いずれかの軸で`min`が`max`を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{IntegralXDBuf, XDBuf};
+    ///
+    /// let buf = XDBuf::<u64, 1>::new_with_vec([4], vec![1, 2, 3, 4]).unwrap();
+    ///
+    /// let integral = IntegralXDBuf::<u64, 1>::new_with_squares(&buf).unwrap();
+    /// assert_eq!(integral.box_square_sum([1], [2]).unwrap(), 13);
+    ///
+    /// let integral = IntegralXDBuf::<u64, 1>::new(&buf).unwrap();
+    /// assert!(integral.box_square_sum([1], [2]).is_err());
+    /// ```
+    pub fn box_square_sum(&self, min: [usize; D], max: [usize; D]) -> Result<A, anyhow::Error> {
+        let squares = self.squares.as_ref().ok_or_else(|| anyhow!("sums of squares are not kept"))?;
+        self.validate_box(&min, &max)?;
+
+        Ok(box_sum(squares, &min, &max))
+    }
+
+    fn validate_box(&self, min: &[usize; D], max: &[usize; D]) -> Result<(), anyhow::Error> {
+        if max.iter().zip(self.size()).any(|(&m, s)| m >= s) {
+            return Err(anyhow!("index is out of range"));
+        }
+        if min.iter().zip(max).any(|(lo, hi)| lo > hi) {
+            return Err(anyhow!("min must not exceed max"));
+        }
+
+        Ok(())
+    }
+}
+
+impl<A: Float, const D: usize> IntegralXDBuf<A, D> {
+    /// Returns the mean of the elements in the box from `min` to `max`, both inclusive.
+    ///
+    /// 両端を含む`min`から`max`までの直方体の要素の平均を返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `max` is out of range.
+    /// * Error if `min` exceeds `max` along an axis.
+    ///
+    /// * `max`が範囲外の場合エラーになります。
+    /// * いずれかの軸で`min`が`max`を超える場合エラーになります。
+    pub fn box_mean(&self, min: [usize; D], max: [usize; D]) -> Result<A, anyhow::Error> {
+        Ok(self.box_sum(min, max)? / box_len(&min, &max))
+    }
+
+    /// Returns the population variance of the elements in the box from `min` to `max`, both inclusive.
+    ///
+    /// 両端を含む`min`から`max`までの直方体の要素の母分散を返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the sums of squared values are not kept.
+    /// * Error if `max` is out of range.
+    /// * Error if `min` exceeds `max` along an axis.
+    ///
+    /// * 二乗した値の和を保持していない場合エラーになります。
+    /// * `max`が範囲外の場合エラーになります。
+    /// * いずれかの軸で`min`が`max`を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{IntegralXDBuf, XDBuf};
+    ///
+    /// let buf = XDBuf::<f64, 2>::new_with_vec([2, 2], vec![1.0, 3.0, 1.0, 3.0]).unwrap();
+    /// let integral = IntegralXDBuf::<f64, 2>::new_with_squares(&buf).unwrap();
+    ///
+    /// assert_eq!(integral.box_mean([0, 0], [1, 1]).unwrap(), 2.0);
+    /// assert_eq!(integral.box_variance([0, 0], [1, 1]).unwrap(), 1.0);
+    /// ```
+    pub fn box_variance(&self, min: [usize; D], max: [usize; D]) -> Result<A, anyhow::Error> {
+        let squares = self.box_square_sum(min, max)?;
+        let mean = self.box_mean(min, max)?;
+
+        // 丸め誤差で負にならないようにする
+        Ok((squares / box_len(&min, &max) - mean * mean).max(A::zero()))
+    }
+}
+
+/// Returns the number of elements in the box from `min` to `max` as `A`.
+///
+/// `min`から`max`までの直方体の要素数を`A`として返します。
+fn box_len<A: Float, const D: usize>(min: &[usize; D], max: &[usize; D]) -> A {
+    // 浮動小数点数型への変換は失敗しない
+    min.iter().zip(max).map(|(lo, hi)| A::from(hi - lo + 1).unwrap()).fold(A::one(), |acc, n| acc * n)
+}
+
+/// Reinitializes `table` with the prefix sums of `src` transformed by `f`.
+///
+/// `f`で変換した`src`の累積和で`table`を再初期化します。
+fn accumulate<T, A, const D: usize, S: Storage<T>>(table: &mut XDBuf<A, D>, src: &XDBuf<T, D, S>, f: impl Fn(T) -> A) -> Result<(), anyhow::Error>
+where
+    T: Copy,
+    A: Copy + Zero + Add<Output = A>,
+{
+    let mut size = src.size();
+    for s in &mut size {
+        *s = s.checked_add(1).ok_or_else(|| anyhow!("size is out of range"))?;
+    }
+    table.init(size, A::zero())?;
+
+    let stride = table.stride;
+    for (index, &v) in src.indexed_iter() {
+        let i = index.iter().zip(stride).map(|(&i, s)| (i + 1) * s).sum::<usize>();
+        table.buf[i] = f(v);
+    }
+
+    // 軸ごとに累積する
+    for a in 0..D {
+        for i in 0..table.buf.len() {
+            if !(i / stride[a]).is_multiple_of(size[a]) {
+                table.buf[i] = table.buf[i] + table.buf[i - stride[a]];
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the sum over the box from `min` to `max` from the prefix sums in `table` by inclusion-exclusion.
+///
+/// The corners are differenced one axis at a time, so every intermediate value is itself the sum over a box.
+/// It neither underflows for unsigned types nor overflows unless such a sum does.
+///
+/// 包除原理により、`table`の累積和から`min`から`max`までの直方体の和を返します。
+///
+/// 角は1軸ずつ差分が取られるため、途中の値はそれぞれが直方体の和になります。
+/// 符号なしの型でもアンダーフローせず、そのような和がオーバーフローしない限りオーバーフローしません。
+fn box_sum<A, const D: usize>(table: &XDBuf<A, D>, min: &[usize; D], max: &[usize; D]) -> A
+where
+    A: Copy + Sub<Output = A>,
+{
+    fn difference<A: Copy + Sub<Output = A>, const D: usize>(
+        table: &XDBuf<A, D>,
+        min: &[usize; D],
+        max: &[usize; D],
+        axis: usize,
+        offset: usize,
+    ) -> A {
+        if axis == D {
+            return table.buf[offset];
+        }
+
+        let stride = table.stride[axis];
+        difference(table, min, max, axis + 1, offset + (max[axis] + 1) * stride)
+            - difference(table, min, max, axis + 1, offset + min[axis] * stride)
+    }
+
+    difference(table, min, max, 0, 0)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn box_sum_matches_brute_force_in_3d() {
+        let buf = XDBuf::<u32, 3>::new_with_layout([4, 5, 3], 0, Layout::LastMajor).unwrap()
+            .map_indexed(|[x, y, z], _| (x * 7 + y * 3 + z * 11) as u32 % 13);
+        let integral = IntegralXDBuf::<u32, 3>::new(&buf).unwrap();
+
+        for (min, max) in [([0, 0, 0], [3, 4, 2]), ([1, 2, 0], [2, 4, 1]), ([3, 0, 2], [3, 0, 2])] {
+            let expected = buf.indexed_iter()
+                .filter(|(i, _)| (0..3).all(|a| (min[a]..=max[a]).contains(&i[a])))
+                .map(|(_, &v)| v)
+                .sum::<u32>();
+
+            assert_eq!(integral.box_sum(min, max).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn wide_accumulator_does_not_overflow() {
+        let image = XDBuf::<u8, 2>::new([300, 200], 255).unwrap();
+        let integral = IntegralXDBuf::<u64, 2>::new_with_squares(&image).unwrap();
+
+        assert_eq!(integral.box_sum([0, 0], [299, 199]).unwrap(), 255 * 300 * 200);
+        assert_eq!(integral.box_sum([299, 199], [299, 199]).unwrap(), 255);
+        assert_eq!(integral.box_square_sum([10, 10], [19, 19]).unwrap(), 255 * 255 * 100);
+
+        // 全体の和が収まる限り、途中の値もオーバーフローしない
+        let buf = XDBuf::<u8, 2>::new([2, 2], 0).unwrap().map(|_| u8::MAX / 4);
+        let integral = IntegralXDBuf::<u8, 2>::new(&buf).unwrap();
+        assert_eq!(integral.box_sum([1, 1], [1, 1]).unwrap(), u8::MAX / 4);
+    }
+
+    #[test]
+    fn rejects_invalid_boxes() {
+        let buf = XDBuf::<i32, 2>::new([3, 3], 1).unwrap();
+        let integral = IntegralXDBuf::<i32, 2>::new(&buf).unwrap();
+
+        assert!(integral.box_sum([0, 0], [3, 0]).is_err());
+        assert!(integral.box_sum([2, 0], [1, 0]).is_err());
+        assert!(!integral.has_squares());
+    }
+
+    #[test]
+    fn rebuild_keeps_squares_and_capacity() {
+        let mut buf = XDBuf::<f32, 2>::new([16, 16], 1.0).unwrap();
+        let mut integral = IntegralXDBuf::<f32, 2>::new_with_squares(&buf).unwrap();
+
+        buf.init([4, 4], 0.0).unwrap();
+        buf.set_m([1, 1], 4.0).unwrap();
+        integral.rebuild(&buf).unwrap();
+
+        assert!(integral.sums.capacity() >= 17 * 17);
+        assert_eq!(integral.box_square_sum([0, 0], [1, 1]).unwrap(), 16.0);
+        assert_eq!(integral.box_variance([0, 0], [1, 1]).unwrap(), 3.0);
+    }
+}
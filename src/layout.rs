@@ -0,0 +1,103 @@
+/// Order in which the elements of an `XDBuf` are laid out in memory
+///
+/// `XDBuf`の要素をメモリ上に並べる順序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Layout {
+    /// The first axis varies fastest (Fortran-like). The stride of `[sx, sy, sz]` is `[1, sx, sx*sy]`.
+    ///
+    /// 最初の軸が最も速く変化します(Fortran形式)。`[sx, sy, sz]`のストライドは`[1, sx, sx*sy]`になります。
+    #[default]
+    FirstMajor,
+    /// The last axis varies fastest (C-like, NumPy default). The stride of `[sx, sy, sz]` is `[sy*sz, sz, 1]`.
+    ///
+    /// 最後の軸が最も速く変化します(C形式、NumPyの既定)。`[sx, sy, sz]`のストライドは`[sy*sz, sz, 1]`になります。
+    LastMajor,
+}
+
+impl Layout {
+    /// Returns the axes of a `D`-dimensional buffer ordered from the fastest-varying to the slowest-varying.
+    ///
+    /// `D`次元のバッファの軸を、最も速く変化するものから順に返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::Layout;
+    ///
+    /// assert_eq!(Layout::FirstMajor.axes_fast_to_slow::<3>(), [0, 1, 2]);
+    /// assert_eq!(Layout::LastMajor.axes_fast_to_slow::<3>(), [2, 1, 0]);
+    /// ```
+    pub fn axes_fast_to_slow<const D: usize>(&self) -> [usize; D] {
+        let mut axes = [0; D];
+
+        for (i, axis) in axes.iter_mut().enumerate() {
+            *axis = match self {
+                Layout::FirstMajor => i,
+                Layout::LastMajor => D - 1 - i,
+            };
+        }
+
+        axes
+    }
+
+    /// Returns the slowest-varying axis of a `D`-dimensional buffer.
+    ///
+    /// `D`次元のバッファで最も変化の遅い軸を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::Layout;
+    ///
+    /// assert_eq!(Layout::FirstMajor.slowest_axis::<3>(), 2);
+    /// assert_eq!(Layout::LastMajor.slowest_axis::<3>(), 0);
+    /// ```
+    pub fn slowest_axis<const D: usize>(&self) -> usize {
+        match self {
+            Layout::FirstMajor => D.saturating_sub(1),
+            Layout::LastMajor => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::XDBuf;
+
+    use super::*;
+
+    #[test]
+    fn last_major_index_conversion_round_trips() {
+        let buf = XDBuf::<i32, 3>::new_with_layout([3, 4, 5], 0, Layout::LastMajor).unwrap();
+
+        for i in buf.idx_range() {
+            assert_eq!(buf.to_scalar_index(&buf.to_mul_dim_index(i)).unwrap(), i);
+        }
+        assert_eq!(buf.to_mul_dim_index(1), [0, 0, 1]);
+        assert_eq!(buf.to_mul_dim_index(5), [0, 1, 0]);
+    }
+
+    #[test]
+    fn walker_moves_in_last_major_layout() {
+        let buf = XDBuf::<i32, 2>::new_with_vec([3, 3], (1..=9).collect()).unwrap();
+        let relayout = buf.relayout(Layout::LastMajor);
+
+        let mut walker = relayout.walker_from_m([1, 1]).unwrap();
+        walker.as_(&[1, 0]).unwrap();
+        assert_eq!(walker.index_m(), [2, 1]);
+        assert_eq!(relayout.get(walker.index_s()), Some(&6));
+
+        walker.as_next().unwrap();
+        assert_eq!(walker.index_m(), [2, 2]);
+        assert_eq!(relayout.get(walker.index_s()), Some(&9));
+    }
+
+    #[test]
+    fn relayout_round_trips() {
+        let buf = XDBuf::<usize, 3>::new_with_vec([2, 3, 4], (0..24).collect()).unwrap();
+        let back = buf.relayout(Layout::LastMajor).relayout(Layout::FirstMajor);
+
+        assert_eq!(back.buf, buf.buf);
+        assert_eq!(back.stride(), buf.stride());
+    }
+}
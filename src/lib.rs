@@ -1,9 +1,54 @@
 pub use step::step2d;
 pub use step::step3d;
-pub use walker::Walker;
+pub use walker::{Walkable, Walker};
+pub use chunked::{ChunkedGrid, ChunkedWalker};
+pub use contour::Polyline;
+pub use dynamic::{DynIndex, WalkerDyn, XDBufDyn};
+pub use integral::IntegralXDBuf;
+pub use layout::Layout;
+pub use offset::OffsetXDBuf;
+pub use pad::PadMode;
+pub use pool::{PooledXDBuf, XDBufPool};
+pub use resample::Filter;
+pub use sample::{Boundary, Interp};
+pub use scrolling::ScrollingXDBuf;
+pub use shape::FillRule;
+pub use sparse::SparseXDBuf;
+pub use stats::Stats;
+pub use storage::{Storage, StorageMut};
+pub use tiled::TiledXDBuf;
+pub use transform::Symmetry;
 pub use xdbuf::XDBuf;
+#[cfg(feature = "rayon")]
+pub use par::LaneMut;
 
 pub mod xdbuf;
+pub mod layout;
 pub mod walker;
 pub mod step;
+pub mod tiled;
+pub mod sparse;
+pub mod storage;
+pub mod pool;
+pub mod dynamic;
+pub mod offset;
+pub mod chunked;
+pub mod scrolling;
+pub mod sample;
+pub mod resample;
+pub mod line;
+pub mod raycast;
+pub mod shape;
+pub mod transform;
+pub mod pad;
+pub mod integral;
+pub mod stats;
+pub mod contour;
+mod blit;
+mod delimited;
+mod map;
+mod ops;
+mod reduce;
+#[cfg(feature = "rayon")]
+pub mod par;
 
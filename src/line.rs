@@ -0,0 +1,243 @@
+use anyhow::anyhow;
+
+use crate::storage::StorageMut;
+use crate::walker::{Walkable, Walker};
+use crate::XDBuf;
+
+/// Iterator over the indices in array notation of a line rasterized with the n-dimensional Bresenham algorithm
+///
+/// Both end points are included, and consecutive indices differ by at most 1 along each axis.
+///
+/// n次元のBresenhamのアルゴリズムでラスタライズした直線の、配列表記のインデックスを走査するイテレータです。
+///
+/// 両端を含み、連続するインデックスは各軸について最大1だけ異なります。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::line::Line;
+///
+/// let line = Line::new([0, 0], [4, 2]).collect::<Vec<_>>();
+/// assert_eq!(line, vec![[0, 0], [1, 0], [2, 1], [3, 1], [4, 2]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Line<const D: usize> {
+    current: [usize; D],
+    delta: [usize; D],
+    forward: [bool; D],
+    error: [usize; D],
+    steps: usize,
+    remaining: usize,
+}
+
+impl<const D: usize> Line<D> {
+    /// Generate a line from `from` to `to`.
+    ///
+    /// `from`から`to`までの直線を生成します。
+    pub fn new(from: [usize; D], to: [usize; D]) -> Self {
+        let delta = std::array::from_fn(|a| from[a].abs_diff(to[a]));
+        let steps = delta.iter().copied().max().unwrap_or(0);
+
+        Self {
+            current: from,
+            delta,
+            forward: std::array::from_fn(|a| to[a] >= from[a]),
+            error: [steps / 2; D],
+            steps,
+            remaining: steps + 1,
+        }
+    }
+}
+
+impl<const D: usize> Iterator for Line<D> {
+    type Item = [usize; D];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let index = self.current;
+        self.remaining -= 1;
+
+        if self.remaining > 0 {
+            for a in 0..D {
+                // 誤差が負になる場合に1つ進む
+                if self.error[a] < self.delta[a] {
+                    self.error[a] += self.steps - self.delta[a];
+                    self.current[a] = if self.forward[a] { self.current[a] + 1 } else { self.current[a] - 1 };
+                } else {
+                    self.error[a] -= self.delta[a];
+                }
+            }
+        }
+
+        Some(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<const D: usize> ExactSizeIterator for Line<D> {}
+
+impl<'a, T, const D: usize, B: Walkable<T, D> + ?Sized> Walker<'a, T, D, B> {
+    /// Returns an iterator over the scalar indices of the line from the current index to `target`.
+    ///
+    /// Both the current index and `target` are included.
+    ///
+    /// 現在のインデックスから`target`までの直線のスカラーのインデックスを走査するイテレータを返します。
+    ///
+    /// 現在のインデックスと`target`の両方を含みます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `target` is out of range.
+    ///
+    /// * `target`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new([3, 3], 0).unwrap();
+    /// let walker = buf.walker_from_m([0, 0]).unwrap();
+    ///
+    /// let diagonal = walker.line_to([2, 2]).unwrap().collect::<Vec<_>>();
+    /// assert_eq!(diagonal, vec![0, 4, 8]);
+    /// ```
+    pub fn line_to(&self, target: [usize; D]) -> Result<impl Iterator<Item = usize> + 'a, anyhow::Error> {
+        let size = self.buf_into.size();
+        if target.iter().zip(size).any(|(&t, s)| t >= s) {
+            return Err(anyhow!("index is out of range"));
+        }
+
+        let buf_into = self.buf_into;
+
+        // 両端が範囲内なので、直線上のインデックスもすべて範囲内
+        Ok(Line::new(self.index_m(), target).map(move |index| buf_into.to_scalar_index(&index).unwrap()))
+    }
+
+    /// Traverses the line from the current index to `target` and returns the first index that satisfies the condition.
+    ///
+    /// Returns `None` if no element on the line satisfies the condition, such as when the line of sight is not blocked.
+    ///
+    /// 現在のインデックスから`target`までの直線を走査し、条件を満たす最初のインデックスを返します。
+    ///
+    /// 視線が遮られない場合など、直線上に条件を満たす要素がない場合は`None`を返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `target` is out of range.
+    ///
+    /// * `target`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// // 1は壁
+    /// let map = XDBuf::<u8, 2>::new_with_vec([4, 2], vec![
+    ///     0, 0, 1, 0,
+    ///     0, 0, 0, 0,
+    /// ]).unwrap();
+    ///
+    /// let walker = map.walker_from_m([0, 0]).unwrap();
+    ///
+    /// let blocking = walker.line_until([3, 0], |&v, _| v == 1).unwrap();
+    /// assert_eq!(blocking, Some(2));
+    ///
+    /// let blocking = walker.line_until([3, 1], |&v, _| v == 1).unwrap();
+    /// assert_eq!(blocking, None);
+    /// ```
+    pub fn line_until(&self, target: [usize; D], f: impl Fn(&T, usize) -> bool) -> Result<Option<usize>, anyhow::Error> {
+        let buf_into = self.buf_into;
+
+        // 直線上のインデックスはすべて範囲内
+        Ok(self.line_to(target)?.find(|&i| f(buf_into.get(i).unwrap(), i)))
+    }
+}
+
+impl<T, const D: usize, S: StorageMut<T>> XDBuf<T, D, S> {
+    /// Sets `value` to every element on the line from `a` to `b`, including both ends.
+    ///
+    /// `a`から`b`までの両端を含む直線上のすべての要素に`value`を設定します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `a` or `b` is out of range.
+    ///
+    /// * `a`または`b`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new([4, 3], 0).unwrap();
+    /// buf.draw_line([0, 2], [3, 0], 1).unwrap();
+    ///
+    /// assert_eq!(buf.as_slice(), &[
+    ///     0, 0, 0, 1,
+    ///     0, 1, 1, 0,
+    ///     1, 0, 0, 0,
+    /// ]);
+    /// ```
+    pub fn draw_line(&mut self, a: [usize; D], b: [usize; D], value: T) -> Result<(), anyhow::Error>
+    where
+        T: Clone,
+    {
+        self.validate_index(&a)?;
+        self.validate_index(&b)?;
+
+        for index in Line::new(a, b) {
+            // 両端が範囲内なので、直線上のインデックスもすべて範囲内
+            let scalar = self.to_scalar_index(&index)?;
+            self.as_mut_slice()[scalar] = value.clone();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn line_is_connected_and_reversible_in_3d() {
+        let from = [7, 0, 3];
+        let to = [0, 5, 9];
+
+        let line = Line::new(from, to).collect::<Vec<_>>();
+        assert_eq!(line.len(), 8);
+        assert_eq!(line.first(), Some(&from));
+        assert_eq!(line.last(), Some(&to));
+
+        for pair in line.windows(2) {
+            assert!(pair[0].iter().zip(pair[1]).all(|(&a, b)| a.abs_diff(b) <= 1));
+        }
+
+        let mut reversed = Line::new(to, from).collect::<Vec<_>>();
+        reversed.reverse();
+        assert_eq!(reversed.len(), line.len());
+    }
+
+    #[test]
+    fn single_point_line() {
+        assert_eq!(Line::new([2, 2], [2, 2]).collect::<Vec<_>>(), vec![[2, 2]]);
+    }
+
+    #[test]
+    fn line_to_rejects_target_out_of_range() {
+        let buf = XDBuf::<i32, 2>::new([3, 3], 0).unwrap();
+        let walker = buf.walker_from_m([0, 0]).unwrap();
+
+        assert!(walker.line_to([3, 0]).is_err());
+        assert!(walker.line_until([0, 3], |_, _| true).is_err());
+        assert_eq!(walker.line_until([2, 0], |_, i| i == 1).unwrap(), Some(1));
+    }
+}
@@ -0,0 +1,229 @@
+use std::marker::PhantomData;
+
+use anyhow::anyhow;
+
+use crate::storage::{Storage, StorageMut};
+use crate::xdbuf::to_mul_dim_index;
+use crate::XDBuf;
+
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Generate a new `XDBuf` by applying `f` to each element.
+    ///
+    /// The result has the size and memory layout of this buffer.
+    ///
+    /// 各要素に`f`を適用して新しい`XDBuf`を生成します。
+    ///
+    /// 結果はこのバッファのサイズとメモリ配置を持ちます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![0, 51, 102, 255]).unwrap();
+    /// let normalized: XDBuf<f32, 2> = buf.map(|&v| v as f32 / 255.0);
+    ///
+    /// assert_eq!(normalized.as_slice(), &[0.0, 0.2, 0.4, 1.0]);
+    /// ```
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> XDBuf<U, D> {
+        XDBuf {
+            buf: self.iter().map(f).collect(),
+            size: self.size,
+            stride: self.stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Generate a new `XDBuf` by applying `f` to each element and its index in array notation.
+    ///
+    /// 各要素と配列表記のインデックスに`f`を適用して新しい`XDBuf`を生成します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new([3, 2], 10).unwrap();
+    /// let mapped = buf.map_indexed(|[x, y], &v| v + x as i32 * 2 + y as i32);
+    ///
+    /// assert_eq!(mapped.as_slice(), &[10, 12, 14, 11, 13, 15]);
+    /// ```
+    pub fn map_indexed<U>(&self, mut f: impl FnMut([usize; D], &T) -> U) -> XDBuf<U, D> {
+        XDBuf {
+            buf: self.indexed_iter().map(|(index, v)| f(index, v)).collect(),
+            size: self.size,
+            stride: self.stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Generate a new `XDBuf` by applying `f` to the elements of this buffer and `other` at the same index.
+    ///
+    /// The memory layouts of the two buffers may differ. The result has the memory layout of this buffer.
+    ///
+    /// このバッファと`other`の同じインデックスの要素に`f`を適用して新しい`XDBuf`を生成します。
+    ///
+    /// 2つのバッファのメモリ配置は異なっていても構いません。結果はこのバッファのメモリ配置を持ちます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the sizes of the two buffers differ.
+    ///
+    /// * 2つのバッファのサイズが異なる場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let a = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// let b = XDBuf::<bool, 2>::new_with_vec([2, 2], vec![true, false, false, true]).unwrap();
+    ///
+    /// let masked = a.zip_with(&b, |&v, &m| if m { v } else { 0 }).unwrap();
+    /// assert_eq!(masked.as_slice(), &[1, 0, 0, 4]);
+    ///
+    /// let c = XDBuf::<bool, 2>::new([2, 3], true).unwrap();
+    /// assert!(a.zip_with(&c, |&v, _| v).is_err());
+    /// ```
+    pub fn zip_with<V, S2: Storage<V>, U>(
+        &self,
+        other: &XDBuf<V, D, S2>,
+        mut f: impl FnMut(&T, &V) -> U,
+    ) -> Result<XDBuf<U, D>, anyhow::Error> {
+        if self.size != other.size {
+            return Err(anyhow!("shape mismatch: {:?} and {:?} differ", self.size, other.size));
+        }
+
+        let buf = if self.layout == other.layout {
+            self.iter().zip(other.iter()).map(|(a, b)| f(a, b)).collect()
+        } else {
+            self.iter().enumerate().map(|(i, a)| {
+                let index = to_mul_dim_index(i, &self.stride, self.layout);
+                let scalar = index.iter().zip(other.stride).map(|(&i, s)| i * s).sum::<usize>();
+                f(a, &other.as_slice()[scalar])
+            }).collect()
+        };
+
+        Ok(XDBuf {
+            buf,
+            size: self.size,
+            stride: self.stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Writes the result of applying `f` to each element into `dest`.
+    ///
+    /// `dest` is resized to the size of this buffer while keeping its memory layout,
+    /// and like `init`, its allocated capacity is reused.
+    ///
+    /// 各要素に`f`を適用した結果を`dest`に書き込みます。
+    ///
+    /// `dest`はメモリ配置を保ったままこのバッファのサイズに変更され、
+    /// `init`と同様に割り当て済みの容量が再利用されます。
+    ///
+    /// If `f` panics, `dest` is left with elements that do not match its size.
+    /// It must not be used until it is reinitialized, for example with `init` or `map_into`.
+    ///
+    /// `f`がパニックした場合、`dest`の要素はサイズと一致しない状態のまま残ります。
+    /// `init`や`map_into`などで再初期化するまで使用しないでください。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut dest = XDBuf::<f32, 2>::new([8, 8], 0.0).unwrap();
+    ///
+    /// for frame in 1..=3_u8 {
+    ///     let buf = XDBuf::<u8, 2>::new([4, 4], frame).unwrap();
+    ///     buf.map_into(&mut dest, |&v| v as f32 * 0.5);
+    /// }
+    ///
+    /// assert_eq!(dest.size(), [4, 4]);
+    /// assert_eq!(dest.get(0), Some(&1.5));
+    /// ```
+    pub fn map_into<U>(&self, dest: &mut XDBuf<U, D>, mut f: impl FnMut(&T) -> U) {
+        let same_layout = dest.layout == self.layout;
+        let layout = dest.layout;
+
+        // サイズは検証済みなので、別の配置でも再初期化は失敗しない
+        dest.reinit_with(self.size, |buf, len, stride| {
+            if same_layout {
+                buf.extend(self.iter().map(f));
+            } else {
+                buf.extend((0..len).map(|i| {
+                    let index = to_mul_dim_index(i, stride, layout);
+                    let scalar = index.iter().zip(self.stride).map(|(&i, s)| i * s).sum::<usize>();
+                    f(&self.as_slice()[scalar])
+                }));
+            }
+        }).unwrap();
+    }
+}
+
+impl<T, const D: usize, S: StorageMut<T>> XDBuf<T, D, S> {
+    /// Replaces each element with the result of applying `f` to it.
+    ///
+    /// 各要素を、`f`を適用した結果で置き換えます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![1, -2, 3, -4]).unwrap();
+    /// buf.map_inplace(|&v| v.abs());
+    ///
+    /// assert_eq!(buf.as_slice(), &[1, 2, 3, 4]);
+    /// ```
+    pub fn map_inplace(&mut self, mut f: impl FnMut(&T) -> T) {
+        self.iter_mut().for_each(|v| *v = f(v));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn zip_with_matches_indices_across_layouts() {
+        let a = XDBuf::<usize, 3>::new_with_vec([2, 3, 4], (0..24).collect()).unwrap();
+        let b = a.relayout(Layout::LastMajor);
+
+        let zipped = b.zip_with(&a, |&x, &y| x == y).unwrap();
+        assert_eq!(zipped.layout(), Layout::LastMajor);
+        assert!(zipped.iter().all(|&v| v));
+    }
+
+    #[test]
+    fn map_into_reuses_allocation_and_keeps_destination_layout() {
+        let src = XDBuf::<usize, 2>::new_with_vec([3, 2], (0..6).collect()).unwrap();
+        let mut dest = XDBuf::<usize, 2>::new_with_layout([10, 10], 0, Layout::LastMajor).unwrap();
+        let ptr = dest.as_slice().as_ptr();
+
+        src.map_into(&mut dest, |&v| v * 10);
+
+        assert_eq!(dest.as_slice().as_ptr(), ptr);
+        assert_eq!(dest.layout(), Layout::LastMajor);
+        assert_eq!(dest.size(), [3, 2]);
+        for (index, v) in src.indexed_iter() {
+            assert_eq!(dest.get_m(index), Some(&(v * 10)));
+        }
+    }
+
+    #[test]
+    fn map_indexed_passes_indices_in_memory_order() {
+        let buf = XDBuf::<u8, 2>::new_with_layout([2, 3], 0, Layout::LastMajor).unwrap();
+        let mapped = buf.map_indexed(|index, _| index);
+
+        for (index, v) in mapped.indexed_iter() {
+            assert_eq!(&index, v);
+        }
+    }
+}
@@ -0,0 +1,510 @@
+use anyhow::anyhow;
+
+use crate::walker::{Walkable, Walker};
+use crate::XDBuf;
+
+/// Structure representing an n-dimensional buffer addressed by signed world coordinates
+///
+/// The element at index `[0; D]` of the inner `XDBuf` is located at the world coordinate `origin`.
+///
+/// 符号付きのワールド座標でアクセスするn次元のバッファを表す構造体です。
+///
+/// 内部の`XDBuf`のインデックス`[0; D]`の要素は、ワールド座標`origin`に位置します。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::OffsetXDBuf;
+///
+/// // (-2, -2)から(2, 2)までの範囲
+/// let mut map = OffsetXDBuf::<u8, 2>::new([5, 5], [-2, -2], 0).unwrap();
+/// map.set_world([-2, 1], 1).unwrap();
+///
+/// // 範囲外を探索すると、データを保ったまま拡張される
+/// map.extend_to_include([4, -3], 0).unwrap();
+/// assert!(map.contains([4, -3]));
+/// assert_eq!(map.get_world([-2, 1]), Some(&1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct OffsetXDBuf<T, const D: usize> {
+    buf: XDBuf<T, D>,
+    origin: [isize; D],
+}
+
+impl<T, const D: usize> OffsetXDBuf<T, D> {
+    /// Generate a new `OffsetXDBuf` covering `size` elements from the world coordinate `origin`.
+    ///
+    /// ワールド座標`origin`から`size`個の要素を覆う新しい`OffsetXDBuf`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    /// * Error if the covered world coordinates exceed the range of `isize`.
+    ///
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    /// * 覆われるワールド座標が`isize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::OffsetXDBuf;
+    ///
+    /// let buf = OffsetXDBuf::<i32, 2>::new([3, 3], [-1, -1], 0).unwrap();
+    ///
+    /// assert!(buf.contains([-1, 1]));
+    /// assert!(!buf.contains([2, 0]));
+    /// ```
+    pub fn new(size: [usize; D], origin: [isize; D], initial_value: T) -> Result<Self, anyhow::Error>
+    where
+        T: Clone,
+    {
+        Self::from_xdbuf(XDBuf::new(size, initial_value)?, origin)
+    }
+
+    /// Generate an `OffsetXDBuf` placing `buf` at the world coordinate `origin`.
+    ///
+    /// `buf`をワールド座標`origin`に配置した`OffsetXDBuf`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the covered world coordinates exceed the range of `isize`.
+    ///
+    /// * 覆われるワールド座標が`isize`の範囲を超える場合エラーになります。
+    pub fn from_xdbuf(buf: XDBuf<T, D>, origin: [isize; D]) -> Result<Self, anyhow::Error> {
+        Self::validate_bounds(&buf.size(), &origin)?;
+
+        Ok(Self { buf, origin })
+    }
+
+    /// Returns the world coordinate of the element at index `[0; D]`.
+    ///
+    /// インデックス`[0; D]`の要素のワールド座標を返します。
+    pub fn origin(&self) -> [isize; D] {
+        self.origin
+    }
+
+    /// Returns the size of each dimension of the buffer.
+    ///
+    /// バッファの各次元のサイズを返します。
+    pub fn size(&self) -> [usize; D] {
+        self.buf.size()
+    }
+
+    /// Returns the number of elements in the buffer.
+    ///
+    /// バッファの要素数を返します。
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if the buffer contains no elements.
+    ///
+    /// バッファが要素を持たない場合に`true`を返します。
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Returns a reference to the inner `XDBuf`.
+    ///
+    /// 内部の`XDBuf`への参照を返します。
+    pub fn as_xdbuf(&self) -> &XDBuf<T, D> {
+        &self.buf
+    }
+
+    /// Returns a mutable reference to the elements of the inner `XDBuf`.
+    ///
+    /// 内部の`XDBuf`の要素への可変な参照を返します。
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.buf.as_mut_slice()
+    }
+
+    /// Consumes the buffer and returns the inner `XDBuf`.
+    ///
+    /// バッファを消費し、内部の`XDBuf`を返します。
+    pub fn into_xdbuf(self) -> XDBuf<T, D> {
+        self.buf
+    }
+
+    /// Returns `true` if the world coordinate `world` is inside the buffer.
+    ///
+    /// ワールド座標`world`がバッファの内側にある場合に`true`を返します。
+    pub fn contains(&self, world: [isize; D]) -> bool {
+        self.to_local(world).is_some()
+    }
+
+    /// Convert the world coordinate `world` to an index in array notation of the inner `XDBuf`.
+    ///
+    /// Returns `None` if `world` is outside the buffer.
+    ///
+    /// ワールド座標`world`を内部の`XDBuf`の配列表記のインデックスに変換します。
+    ///
+    /// `world`がバッファの外側にある場合は`None`を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::OffsetXDBuf;
+    ///
+    /// let buf = OffsetXDBuf::<i32, 2>::new([4, 4], [-2, 10], 0).unwrap();
+    ///
+    /// assert_eq!(buf.to_local([-1, 13]), Some([1, 3]));
+    /// assert_eq!(buf.to_local([-3, 10]), None);
+    /// ```
+    pub fn to_local(&self, world: [isize; D]) -> Option<[usize; D]> {
+        to_local(world, &self.origin, &self.buf.size())
+    }
+
+    /// Convert an index in array notation of the inner `XDBuf` to a world coordinate.
+    ///
+    /// 内部の`XDBuf`の配列表記のインデックスをワールド座標に変換します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::OffsetXDBuf;
+    ///
+    /// let buf = OffsetXDBuf::<i32, 2>::new([4, 4], [-2, 10], 0).unwrap();
+    /// assert_eq!(buf.to_world([1, 3]), [-1, 13]);
+    /// ```
+    pub fn to_world(&self, local: [usize; D]) -> [isize; D] {
+        let mut world = self.origin;
+
+        // 内側のインデックスについては生成時に範囲を検証済み
+        world.iter_mut().zip(local).for_each(|(w, l)| *w = w.wrapping_add_unsigned(l));
+
+        world
+    }
+
+    /// Get a reference to the element at the world coordinate `world`.
+    ///
+    /// Returns `None` if `world` is outside the buffer.
+    ///
+    /// ワールド座標`world`の要素の参照を取得します。
+    ///
+    /// `world`がバッファの外側にある場合は`None`を返します。
+    pub fn get_world(&self, world: [isize; D]) -> Option<&T> {
+        self.buf.get_m(self.to_local(world)?)
+    }
+
+    /// Get a variable reference to the element at the world coordinate `world`.
+    ///
+    /// Returns `None` if `world` is outside the buffer.
+    ///
+    /// ワールド座標`world`の要素の可変参照を取得します。
+    ///
+    /// `world`がバッファの外側にある場合は`None`を返します。
+    pub fn get_mut_world(&mut self, world: [isize; D]) -> Option<&mut T> {
+        let local = self.to_local(world)?;
+
+        self.buf.get_mut_m(local)
+    }
+
+    /// Set `value` to the element at the world coordinate `world`.
+    ///
+    /// ワールド座標`world`の要素に`value`を設定します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `world` is outside the buffer.
+    ///
+    /// * `world`がバッファの外側にある場合エラーになります。
+    pub fn set_world(&mut self, world: [isize; D], value: T) -> Result<(), anyhow::Error> {
+        *self.get_mut_world(world).ok_or(anyhow!("index is out of range"))? = value;
+
+        Ok(())
+    }
+
+    /// Returns an iterator over the world coordinates and elements in memory order.
+    ///
+    /// ワールド座標と要素をメモリ上の順序で走査するイテレータを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::OffsetXDBuf;
+    ///
+    /// let buf = OffsetXDBuf::<i32, 1>::new([3], [-1], 0).unwrap();
+    /// let coords = buf.indexed_iter_world().map(|(w, _)| w).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(coords, vec![[-1], [0], [1]]);
+    /// ```
+    pub fn indexed_iter_world(&self) -> impl Iterator<Item = ([isize; D], &T)> + '_ {
+        self.buf.indexed_iter().map(move |(index, v)| (self.to_world(index), v))
+    }
+
+    /// Generates a `Walker` with the world coordinate `world` as its initial position.
+    ///
+    /// ワールド座標`world`を初期位置として`Walker`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `world` is outside the buffer.
+    ///
+    /// * `world`がバッファの外側にある場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::OffsetXDBuf;
+    ///
+    /// let buf = OffsetXDBuf::<i32, 2>::new([3, 3], [-1, -1], 0).unwrap();
+    /// let mut walker = buf.walker_from_world([0, 0]).unwrap();
+    ///
+    /// walker.as_(&[-1, 1]).unwrap();
+    /// assert_eq!(walker.index_world(), [-1, 1]);
+    /// assert_eq!(walker.index_m(), [0, 2]);
+    /// ```
+    pub fn walker_from_world(&self, world: [isize; D]) -> Result<Walker<'_, T, D, Self>, anyhow::Error> {
+        let local = self.to_local(world).ok_or(anyhow!("index is out of range"))?;
+
+        Ok(Walker::new(self, self.buf.to_scalar_index(&local)?))
+    }
+
+    /// Moves the buffer so that `center` becomes its center, keeping its size.
+    ///
+    /// The origin becomes `center - size / 2`, so for an even size `center` is the element just after the middle.
+    /// Elements that remain inside keep their world coordinates, and newly covered elements are filled with `fill`.
+    ///
+    /// サイズを保ったまま、`center`が中心になるようにバッファを移動します。
+    ///
+    /// 原点は`center - size / 2`になるため、サイズが偶数の場合`center`は中央の直後の要素になります。
+    /// 内側に残る要素はワールド座標を保ち、新たに覆われる要素は`fill`で埋められます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the covered world coordinates exceed the range of `isize`.
+    ///
+    /// * 覆われるワールド座標が`isize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::OffsetXDBuf;
+    ///
+    /// let mut buf = OffsetXDBuf::<i32, 2>::new([3, 3], [0, 0], 0).unwrap();
+    /// buf.set_world([2, 2], 1).unwrap();
+    ///
+    /// // [2, 2]にいるプレイヤーを中心に据える
+    /// buf.recenter([2, 2], -1).unwrap();
+    /// assert_eq!(buf.origin(), [1, 1]);
+    /// assert_eq!(buf.get_world([2, 2]), Some(&1));
+    /// assert_eq!(buf.get_world([3, 3]), Some(&-1));
+    /// assert_eq!(buf.get_world([0, 0]), None);
+    /// ```
+    pub fn recenter(&mut self, center: [isize; D], fill: T) -> Result<(), anyhow::Error>
+    where
+        T: Clone,
+    {
+        let size = self.buf.size();
+        let mut origin = [0_isize; D];
+
+        for ((o, &c), &s) in origin.iter_mut().zip(&center).zip(&size) {
+            *o = c.checked_sub_unsigned(s / 2).ok_or(anyhow!("size is out of range"))?;
+        }
+
+        self.reframe(size, origin, fill)
+    }
+
+    /// Grows the buffer so that it contains the world coordinate `point`, preserving its elements.
+    ///
+    /// Newly covered elements are filled with `fill`. Nothing happens if `point` is already inside.
+    /// Each side that has to grow is extended by at least the current size along its axis,
+    /// so including neighboring points one at a time reallocates only a logarithmic number of times.
+    ///
+    /// 要素を保ったまま、ワールド座標`point`を含むようにバッファを拡張します。
+    ///
+    /// 新たに覆われる要素は`fill`で埋められます。`point`が既に内側にある場合は何もしません。
+    /// 拡張が必要な側は少なくともその軸の現在のサイズだけ広げられるため、
+    /// 隣接する点を1つずつ含めていく場合でも再割り当ては対数回で済みます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of the new size exceeds the range of `usize`.
+    ///
+    /// * 新しいサイズの総積が`usize`の範囲を超える場合エラーになります。
+    pub fn extend_to_include(&mut self, point: [isize; D], fill: T) -> Result<(), anyhow::Error>
+    where
+        T: Clone,
+    {
+        if self.contains(point) {
+            return Ok(());
+        }
+
+        let mut origin = self.origin;
+        let mut size = self.buf.size();
+
+        for ((o, s), p) in origin.iter_mut().zip(size.iter_mut()).zip(point) {
+            // 生成時に範囲を検証済みなので、最後の要素の座標はオーバーフローしない
+            let end = o.wrapping_add_unsigned(*s - 1);
+
+            // 少なくとも現在のサイズだけ広げ、isizeの範囲で飽和させても`p`は含まれる
+            let new_origin = if p < *o { o.saturating_sub_unsigned(o.abs_diff(p).max(*s)) } else { *o };
+            let new_end = if p > end { end.saturating_add_unsigned(p.abs_diff(end).max(*s)) } else { end };
+
+            *s = new_end.abs_diff(new_origin).checked_add(1).ok_or(
+                anyhow!("size is out of range")
+            )?;
+            *o = new_origin;
+        }
+
+        self.reframe(size, origin, fill)
+    }
+
+    /// Replaces the buffer with one of `size` at `origin`, copying the overlapping elements.
+    ///
+    /// バッファを`origin`に位置する`size`のものに置き換え、重なる要素を複製します。
+    fn reframe(&mut self, size: [usize; D], origin: [isize; D], fill: T) -> Result<(), anyhow::Error>
+    where
+        T: Clone,
+    {
+        Self::validate_bounds(&size, &origin)?;
+
+        let mut buf = XDBuf::new_with_layout(size, fill, self.buf.layout())?;
+
+        for i in self.buf.idx_range() {
+            let world = self.to_world(self.buf.to_mul_dim_index(i));
+
+            if let Some(local) = to_local(world, &origin, &size) {
+                buf.set_m(local, self.buf.as_slice()[i].clone())?;
+            }
+        }
+
+        self.buf = buf;
+        self.origin = origin;
+
+        Ok(())
+    }
+
+    /// Checks that the world coordinates covered by `size` from `origin` fit in `isize`.
+    ///
+    /// `origin`から`size`で覆われるワールド座標が`isize`に収まることをチェックします。
+    fn validate_bounds(size: &[usize; D], origin: &[isize; D]) -> Result<(), anyhow::Error> {
+        let fits = size.iter().zip(origin).all(|(&s, &o)| {
+            s.checked_sub(1).is_some_and(|s| o.checked_add_unsigned(s).is_some())
+        });
+
+        if fits {
+            Ok(())
+        } else {
+            Err(anyhow!("size is out of range"))
+        }
+    }
+}
+
+/// Convert the world coordinate `world` to an index in array notation of a buffer of `size` at `origin`.
+///
+/// `origin`に位置する`size`のバッファについて、ワールド座標`world`を配列表記のインデックスに変換します。
+fn to_local<const D: usize>(world: [isize; D], origin: &[isize; D], size: &[usize; D]) -> Option<[usize; D]> {
+    let mut local = [0; D];
+
+    for (((l, w), &o), &s) in local.iter_mut().zip(world).zip(origin).zip(size) {
+        if w < o || w.abs_diff(o) >= s {
+            return None;
+        }
+
+        *l = w.abs_diff(o);
+    }
+
+    Some(local)
+}
+
+impl<T, const D: usize> Walkable<T, D> for OffsetXDBuf<T, D> {
+    fn size(&self) -> [usize; D] {
+        self.buf.size()
+    }
+
+    fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        self.buf.get(index)
+    }
+
+    fn to_scalar_index(&self, index: &[usize; D]) -> Result<usize, anyhow::Error> {
+        self.buf.to_scalar_index(index)
+    }
+
+    fn to_mul_dim_index(&self, scalar: usize) -> [usize; D] {
+        self.buf.to_mul_dim_index(scalar)
+    }
+}
+
+impl<T, const D: usize> Walker<'_, T, D, OffsetXDBuf<T, D>> {
+    /// Returns the current index as a world coordinate.
+    ///
+    /// 現在のインデックスをワールド座標として返します。
+    pub fn index_world(&self) -> [isize; D] {
+        self.buf_into.to_world(self.index_m())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn extend_to_include_preserves_world_coordinates() {
+        let buf = XDBuf::<i32, 2>::new_with_layout([3, 2], 0, Layout::LastMajor).unwrap();
+        let mut offset = OffsetXDBuf::from_xdbuf(buf, [-1, -1]).unwrap();
+
+        for (i, world) in [[-1, -1], [0, -1], [1, 0]].into_iter().enumerate() {
+            offset.set_world(world, i as i32 + 1).unwrap();
+        }
+
+        offset.extend_to_include([-5, 3], 0).unwrap();
+        offset.extend_to_include([4, -2], 0).unwrap();
+
+        assert_eq!(offset.origin(), [-5, -6]);
+        assert_eq!(offset.size(), [14, 10]);
+        assert_eq!(offset.as_xdbuf().layout(), Layout::LastMajor);
+        assert_eq!(offset.get_world([-1, -1]), Some(&1));
+        assert_eq!(offset.get_world([0, -1]), Some(&2));
+        assert_eq!(offset.get_world([1, 0]), Some(&3));
+        assert_eq!(offset.indexed_iter_world().filter(|(_, &v)| v != 0).count(), 3);
+    }
+
+    #[test]
+    fn extend_to_include_grows_geometrically() {
+        let mut offset = OffsetXDBuf::<u8, 2>::new([1, 1], [0, 0], 0).unwrap();
+        let mut reallocations = 0;
+
+        for x in 1..1000 {
+            let size = offset.size();
+            offset.extend_to_include([x, 0], 0).unwrap();
+            offset.extend_to_include([-x, 0], 0).unwrap();
+
+            if offset.size() != size {
+                reallocations += 1;
+            }
+            assert!(offset.size()[0] <= 4 * (2 * x as usize + 1));
+        }
+
+        assert!(reallocations <= 2 * 11);
+        assert_eq!(offset.size()[1], 1);
+    }
+
+    #[test]
+    fn rejects_coordinates_beyond_isize() {
+        assert!(OffsetXDBuf::<u8, 1>::new([2], [isize::MAX], 0).is_err());
+
+        let mut buf = OffsetXDBuf::<u8, 1>::new([2], [isize::MAX - 1], 0).unwrap();
+        assert_eq!(buf.get_world([isize::MAX]), Some(&0));
+        assert_eq!(buf.get_world([isize::MIN]), None);
+        assert!(buf.recenter([isize::MIN], 0).is_err());
+        assert!(buf.recenter([isize::MAX], 0).is_ok());
+        assert_eq!(buf.origin(), [isize::MAX - 1]);
+    }
+
+    #[test]
+    fn walker_reports_world_coordinates() {
+        let buf = OffsetXDBuf::<u8, 3>::new([2, 2, 2], [-10, 0, 10], 0).unwrap();
+        let mut walker = buf.walker_from_world([-10, 0, 10]).unwrap();
+
+        walker.as_next().unwrap();
+        assert_eq!(walker.index_world(), [-9, 0, 10]);
+        assert!(buf.walker_from_world([-11, 0, 10]).is_err());
+    }
+}
@@ -0,0 +1,421 @@
+use std::marker::PhantomData;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use anyhow::anyhow;
+
+use crate::storage::{Storage, StorageMut};
+use crate::xdbuf::to_mul_dim_index;
+use crate::XDBuf;
+
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Calculates the size resulting from broadcasting buffers of sizes `a` and `b` against each other.
+    ///
+    /// For each dimension the sizes must be equal, or one of them must be 1.
+    ///
+    /// サイズ`a`と`b`のバッファを互いにブロードキャストした結果のサイズを計算します。
+    ///
+    /// 各次元について、サイズが等しいか、どちらか一方が1である必要があります。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the sizes of some dimension are different and neither is 1.
+    ///
+    /// * ある次元のサイズが異なり、どちらも1でない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let size = XDBuf::<f32, 3>::calc_broadcast_size(&[3, 1, 5], &[3, 4, 1]).unwrap();
+    /// assert_eq!(size, [3, 4, 5]);
+    ///
+    /// assert!(XDBuf::<f32, 3>::calc_broadcast_size(&[3, 4, 5], &[2, 4, 5]).is_err());
+    /// ```
+    pub fn calc_broadcast_size(a: &[usize; D], b: &[usize; D]) -> Result<[usize; D], anyhow::Error> {
+        let mut size = [0; D];
+
+        for (i, s) in size.iter_mut().enumerate() {
+            *s = match (a[i], b[i]) {
+                (a, b) if a == b => a,
+                (1, b) => b,
+                (a, 1) => a,
+                (a, b) => return Err(anyhow!("shape mismatch: {} and {} at dimension {} cannot be broadcast", a, b, i)),
+            };
+        }
+
+        Ok(size)
+    }
+
+    /// Generate a new `XDBuf` by applying `f` to pairs of elements of this buffer and `other`, broadcasting dimensions of size 1.
+    ///
+    /// The result has the memory layout of this buffer.
+    ///
+    /// サイズ1の次元をブロードキャストしながら、このバッファと`other`の要素の組に`f`を適用して新しい`XDBuf`を生成します。
+    ///
+    /// 結果はこのバッファのメモリ配置を持ちます。
+    pub(crate) fn broadcast_with<U, R, S2: Storage<U>>(
+        &self,
+        other: &XDBuf<U, D, S2>,
+        mut f: impl FnMut(&T, &U) -> R,
+    ) -> Result<XDBuf<R, D>, anyhow::Error> {
+        let size = Self::calc_broadcast_size(&self.size, &other.size)?;
+        let stride = Self::calc_dim_stride_with_layout(&size, self.layout)?;
+
+        let buf = if self.size == other.size && self.stride == other.stride {
+            self.as_slice().iter().zip(other.as_slice()).map(|(a, b)| f(a, b)).collect()
+        } else {
+            (0..Self::calc_total_size(&size)?).map(|i| {
+                let index = to_mul_dim_index(i, &stride, self.layout);
+                f(&self.as_slice()[broadcast_scalar(&index, &self.size, &self.stride)],
+                  &other.as_slice()[broadcast_scalar(&index, &other.size, &other.stride)])
+            }).collect()
+        };
+
+        Ok(XDBuf {
+            buf,
+            size,
+            stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T, const D: usize, S: StorageMut<T>> XDBuf<T, D, S> {
+    /// Applies `f` to each element of this buffer and the corresponding element of `other`, broadcasting dimensions of size 1 in `other`.
+    ///
+    /// `other`のサイズ1の次元をブロードキャストしながら、このバッファの各要素と`other`の対応する要素に`f`を適用します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `other` cannot be broadcast to the size of this buffer.
+    ///
+    /// * `other`をこのバッファのサイズにブロードキャストできない場合エラーになります。
+    pub(crate) fn broadcast_assign<U, S2: Storage<U>>(
+        &mut self,
+        other: &XDBuf<U, D, S2>,
+        mut f: impl FnMut(&mut T, &U),
+    ) -> Result<(), anyhow::Error> {
+        if Self::calc_broadcast_size(&self.size, &other.size)? != self.size {
+            return Err(anyhow!("shape mismatch: {:?} cannot be broadcast to {:?}", other.size, self.size));
+        }
+
+        if self.size == other.size && self.stride == other.stride {
+            self.as_mut_slice().iter_mut().zip(other.as_slice()).for_each(|(a, b)| f(a, b));
+        } else {
+            let (stride, layout) = (self.stride, self.layout);
+
+            self.as_mut_slice().iter_mut().enumerate().for_each(|(i, a)| {
+                let index = to_mul_dim_index(i, &stride, layout);
+                f(a, &other.as_slice()[broadcast_scalar(&index, &other.size, &other.stride)]);
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the scalar index of the element of a buffer with `size` and `stride` that `index` is broadcast from.
+///
+/// `index`のブロードキャスト元となる、`size`と`stride`を持つバッファの要素のスカラーのインデックスを返します。
+fn broadcast_scalar<const D: usize>(index: &[usize; D], size: &[usize; D], stride: &[usize; D]) -> usize {
+    index.iter().zip(size).zip(stride)
+        .map(|((&i, &s), &st)| if s == 1 { 0 } else { i * st })
+        .sum()
+}
+
+macro_rules! impl_binary_op {
+    ($Op:ident, $op:ident, $OpAssign:ident, $op_assign:ident, $try_op_assign:ident, $doc_en:literal, $doc_ja:literal) => {
+        impl<T, const D: usize, S: StorageMut<T>> XDBuf<T, D, S>
+        where
+            T: $OpAssign + Clone,
+        {
+            #[doc = concat!("Applies `", stringify!($op_assign), "` element-wise with `other`, broadcasting dimensions of size 1 in `other`.")]
+            ///
+            #[doc = concat!("`other`のサイズ1の次元をブロードキャストしながら、要素ごとに", $doc_ja, "を代入します。")]
+            ///
+            /// # Errors
+            ///
+            /// * Error if `other` cannot be broadcast to the size of this buffer.
+            ///
+            /// * `other`をこのバッファのサイズにブロードキャストできない場合エラーになります。
+            pub fn $try_op_assign<S2: Storage<T>>(&mut self, other: &XDBuf<T, D, S2>) -> Result<(), anyhow::Error> {
+                self.broadcast_assign(other, |a, b| a.$op_assign(b.clone()))
+            }
+        }
+
+        #[doc = concat!("Element-wise ", $doc_en, " of buffers, broadcasting dimensions of size 1.")]
+        ///
+        #[doc = concat!("サイズ1の次元をブロードキャストしながら、バッファの要素ごとの", $doc_ja, "を計算します。")]
+        ///
+        /// Returns an error instead of panicking when the sizes cannot be broadcast.
+        ///
+        /// サイズがブロードキャストできない場合は、パニックせずにエラーを返します。
+        impl<T, const D: usize, S1: Storage<T>, S2: Storage<T>> $Op<&XDBuf<T, D, S2>> for &XDBuf<T, D, S1>
+        where
+            T: $Op<Output = T> + Clone,
+        {
+            type Output = Result<XDBuf<T, D>, anyhow::Error>;
+
+            fn $op(self, rhs: &XDBuf<T, D, S2>) -> Self::Output {
+                self.broadcast_with(rhs, |a, b| a.clone().$op(b.clone()))
+            }
+        }
+
+        impl<T, const D: usize, S2: Storage<T>> $Op<&XDBuf<T, D, S2>> for XDBuf<T, D>
+        where
+            T: $Op<Output = T> + Clone,
+        {
+            type Output = Result<XDBuf<T, D>, anyhow::Error>;
+
+            fn $op(mut self, rhs: &XDBuf<T, D, S2>) -> Self::Output {
+                if XDBuf::<T, D>::calc_broadcast_size(&self.size, &rhs.size)? != self.size {
+                    return (&self).$op(rhs);
+                }
+
+                // 結果のサイズが左辺と同じ場合は、左辺の領域を再利用する
+                self.broadcast_assign(rhs, |a, b| *a = a.clone().$op(b.clone()))?;
+                Ok(self)
+            }
+        }
+
+        impl<T, const D: usize> $Op<XDBuf<T, D>> for XDBuf<T, D>
+        where
+            T: $Op<Output = T> + Clone,
+        {
+            type Output = Result<XDBuf<T, D>, anyhow::Error>;
+
+            fn $op(self, rhs: XDBuf<T, D>) -> Self::Output {
+                self.$op(&rhs)
+            }
+        }
+
+        impl<T, const D: usize, S: Storage<T>> $Op<XDBuf<T, D>> for &XDBuf<T, D, S>
+        where
+            T: $Op<Output = T> + Clone,
+        {
+            type Output = Result<XDBuf<T, D>, anyhow::Error>;
+
+            fn $op(self, rhs: XDBuf<T, D>) -> Self::Output {
+                self.$op(&rhs)
+            }
+        }
+
+        #[doc = concat!("Element-wise ", $doc_en, " of a buffer and a scalar.")]
+        ///
+        #[doc = concat!("バッファとスカラーの要素ごとの", $doc_ja, "を計算します。")]
+        impl<T, const D: usize, S: Storage<T>> $Op<T> for &XDBuf<T, D, S>
+        where
+            T: $Op<Output = T> + Clone,
+        {
+            type Output = XDBuf<T, D>;
+
+            fn $op(self, rhs: T) -> Self::Output {
+                XDBuf {
+                    buf: self.as_slice().iter().map(|a| a.clone().$op(rhs.clone())).collect(),
+                    size: self.size,
+                    stride: self.stride,
+                    layout: self.layout,
+                    _marker: PhantomData,
+                }
+            }
+        }
+
+        impl<T, const D: usize> $Op<T> for XDBuf<T, D>
+        where
+            T: $Op<Output = T> + Clone,
+        {
+            type Output = XDBuf<T, D>;
+
+            fn $op(mut self, rhs: T) -> Self::Output {
+                self.buf.iter_mut().for_each(|a| *a = a.clone().$op(rhs.clone()));
+                self
+            }
+        }
+
+        impl<T, const D: usize, S: StorageMut<T>> $OpAssign<T> for XDBuf<T, D, S>
+        where
+            T: $OpAssign + Clone,
+        {
+            fn $op_assign(&mut self, rhs: T) {
+                self.as_mut_slice().iter_mut().for_each(|a| a.$op_assign(rhs.clone()));
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, AddAssign, add_assign, try_add_assign, "sum", "和");
+impl_binary_op!(Sub, sub, SubAssign, sub_assign, try_sub_assign, "difference", "差");
+impl_binary_op!(Mul, mul, MulAssign, mul_assign, try_mul_assign, "product", "積");
+impl_binary_op!(Div, div, DivAssign, div_assign, try_div_assign, "quotient", "商");
+
+macro_rules! impl_scalar_lhs_op {
+    ($($t:ty),*) => {
+        $(
+            impl<const D: usize, S: Storage<$t>> Add<&XDBuf<$t, D, S>> for $t {
+                type Output = XDBuf<$t, D>;
+
+                fn add(self, rhs: &XDBuf<$t, D, S>) -> Self::Output {
+                    rhs.map_scalar_lhs(|a| self + a)
+                }
+            }
+
+            impl<const D: usize, S: Storage<$t>> Sub<&XDBuf<$t, D, S>> for $t {
+                type Output = XDBuf<$t, D>;
+
+                fn sub(self, rhs: &XDBuf<$t, D, S>) -> Self::Output {
+                    rhs.map_scalar_lhs(|a| self - a)
+                }
+            }
+
+            impl<const D: usize, S: Storage<$t>> Mul<&XDBuf<$t, D, S>> for $t {
+                type Output = XDBuf<$t, D>;
+
+                fn mul(self, rhs: &XDBuf<$t, D, S>) -> Self::Output {
+                    rhs.map_scalar_lhs(|a| self * a)
+                }
+            }
+
+            impl<const D: usize, S: Storage<$t>> Div<&XDBuf<$t, D, S>> for $t {
+                type Output = XDBuf<$t, D>;
+
+                fn div(self, rhs: &XDBuf<$t, D, S>) -> Self::Output {
+                    rhs.map_scalar_lhs(|a| self / a)
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_lhs_op!(f32, f64, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<T: Copy, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Generate a new `XDBuf` by applying `f` to each element, for operators with a scalar on the left-hand side.
+    ///
+    /// 左辺がスカラーの演算子のために、各要素に`f`を適用して新しい`XDBuf`を生成します。
+    fn map_scalar_lhs(&self, f: impl Fn(T) -> T) -> XDBuf<T, D> {
+        XDBuf {
+            buf: self.as_slice().iter().map(|&a| f(a)).collect(),
+            size: self.size,
+            stride: self.stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Element-wise negation of a buffer.
+///
+/// バッファの要素ごとの符号反転を計算します。
+impl<T, const D: usize, S: Storage<T>> Neg for &XDBuf<T, D, S>
+where
+    T: Neg<Output = T> + Clone,
+{
+    type Output = XDBuf<T, D>;
+
+    fn neg(self) -> Self::Output {
+        XDBuf {
+            buf: self.as_slice().iter().map(|a| -a.clone()).collect(),
+            size: self.size,
+            stride: self.stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, const D: usize> Neg for XDBuf<T, D>
+where
+    T: Neg<Output = T> + Clone,
+{
+    type Output = XDBuf<T, D>;
+
+    fn neg(mut self) -> Self::Output {
+        self.buf.iter_mut().for_each(|a| *a = -a.clone());
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn adds_buffers_of_identical_size() {
+        let a = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+        let b = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![10, 20, 30, 40]).unwrap();
+
+        let c = (&a + &b).unwrap();
+        assert_eq!(c.as_slice(), &[11, 22, 33, 44]);
+
+        let c = (a - b).unwrap();
+        assert_eq!(c.as_slice(), &[-9, -18, -27, -36]);
+    }
+
+    #[test]
+    fn broadcasts_dimensions_of_size_one() {
+        // [1, 2, 3,
+        //  4, 5, 6]
+        let a = XDBuf::<f32, 2>::new_with_vec([3, 2], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        // 各行の平均
+        let mean = XDBuf::<f32, 2>::new_with_vec([1, 2], vec![2.0, 5.0]).unwrap();
+
+        let centered = (&a - &mean).unwrap();
+        assert_eq!(centered.as_slice(), &[-1.0, 0.0, 1.0, -1.0, 0.0, 1.0]);
+
+        // 左辺がブロードキャストされる場合は新しいバッファが割り当てられる
+        let centered = (mean - &a).unwrap();
+        assert_eq!(centered.size(), [3, 2]);
+        assert_eq!(centered.as_slice(), &[1.0, 0.0, -1.0, 1.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn broadcasts_across_layouts() {
+        let a = XDBuf::<i32, 2>::new_with_vec([3, 2], (1..=6).collect()).unwrap();
+        let b = a.relayout(Layout::LastMajor);
+
+        let c = (&a + &b).unwrap();
+        for (index, v) in c.indexed_iter() {
+            assert_eq!(*v, a.get_m(index).unwrap() * 2);
+        }
+
+        let mut d = b.clone();
+        d.try_mul_assign(&a).unwrap();
+        for (index, v) in d.indexed_iter() {
+            assert_eq!(*v, a.get_m(index).unwrap().pow(2));
+        }
+    }
+
+    #[test]
+    fn returns_error_on_shape_mismatch() {
+        let a = XDBuf::<i32, 2>::new([3, 2], 1).unwrap();
+        let b = XDBuf::<i32, 2>::new([2, 2], 1).unwrap();
+        let row = XDBuf::<i32, 2>::new([3, 1], 1).unwrap();
+
+        assert!((&a + &b).is_err());
+        assert!((a.clone() * b).is_err());
+
+        let mut row2 = row.clone();
+        assert!(row2.try_add_assign(&a).is_err());
+
+        let mut a = a;
+        a.try_add_assign(&row).unwrap();
+        assert!(a.iter().all(|&v| v == 2));
+    }
+
+    #[test]
+    fn applies_scalars_and_negation() {
+        let mut a = XDBuf::<f32, 2>::new_with_vec([2, 1], vec![1.0, 2.0]).unwrap();
+
+        assert_eq!((&a * 0.5).as_slice(), &[0.5, 1.0]);
+        assert_eq!((1.0 - &a).as_slice(), &[0.0, -1.0]);
+        assert_eq!((-&a).as_slice(), &[-1.0, -2.0]);
+
+        a += 1.0;
+        a /= 2.0;
+        assert_eq!(a.as_slice(), &[1.0, 1.5]);
+    }
+}
@@ -0,0 +1,247 @@
+use std::marker::PhantomData;
+
+use anyhow::anyhow;
+
+use crate::storage::Storage;
+use crate::xdbuf::to_mul_dim_index;
+use crate::XDBuf;
+
+/// Value used for the elements added by padding
+///
+/// パディングで追加される要素に使用する値
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadMode<T> {
+    /// The given value.
+    ///
+    /// 指定された値。
+    Constant(T),
+    /// The value of the nearest element on the edge.
+    ///
+    /// 端にある最も近い要素の値。
+    Edge,
+    /// The value mirrored at the edge element, without repeating the edge element.
+    ///
+    /// 端の要素を繰り返さずに、端の要素で鏡映した値。
+    Reflect,
+    /// The value of the element on the opposite side, as if the buffer repeated periodically.
+    ///
+    /// バッファが周期的に繰り返されるものとした、反対側の要素の値。
+    Wrap,
+}
+
+impl<T: Clone, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Generate a copy of the buffer with `before` elements added before and `after` elements added after each axis.
+    ///
+    /// The result has the memory layout of this buffer.
+    ///
+    /// 各軸の前に`before`個、後ろに`after`個の要素を追加したバッファの複製を生成します。
+    ///
+    /// 結果はこのバッファのメモリ配置を持ちます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of the padded size exceeds the range of `usize`.
+    ///
+    /// * パディング後のサイズの総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{PadMode, XDBuf};
+    ///
+    /// let buf = XDBuf::<u8, 1>::new_with_vec([3], vec![1, 2, 3]).unwrap();
+    ///
+    /// assert_eq!(buf.pad([2], [2], PadMode::Constant(0)).unwrap().as_slice(), &[0, 0, 1, 2, 3, 0, 0]);
+    /// assert_eq!(buf.pad([2], [2], PadMode::Edge).unwrap().as_slice(), &[1, 1, 1, 2, 3, 3, 3]);
+    /// assert_eq!(buf.pad([2], [2], PadMode::Reflect).unwrap().as_slice(), &[3, 2, 1, 2, 3, 2, 1]);
+    /// assert_eq!(buf.pad([2], [2], PadMode::Wrap).unwrap().as_slice(), &[2, 3, 1, 2, 3, 1, 2]);
+    /// ```
+    pub fn pad(&self, before: [usize; D], after: [usize; D], mode: PadMode<T>) -> Result<XDBuf<T, D>, anyhow::Error> {
+        // バッファは空にならない
+        let mut dest = XDBuf::new_with_layout([1; D], self.as_slice()[0].clone(), self.layout)?;
+        self.pad_into(&mut dest, before, after, mode)?;
+
+        Ok(dest)
+    }
+
+    /// Writes this buffer padded with `before` and `after` elements on each axis into `dest`.
+    ///
+    /// `dest` is reinitialized with `init`, so its memory layout is kept and its allocated capacity is reused.
+    ///
+    /// 各軸に`before`個と`after`個の要素をパディングしたこのバッファを`dest`に書き込みます。
+    ///
+    /// `dest`は`init`で再初期化されるため、メモリ配置が保たれ、割り当て済みの容量が再利用されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of the padded size exceeds the range of `usize`.
+    ///
+    /// * パディング後のサイズの総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{PadMode, XDBuf};
+    ///
+    /// let buf = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// let mut dest = XDBuf::<u8, 2>::new([8, 8], 0).unwrap();
+    ///
+    /// buf.pad_into(&mut dest, [1, 0], [0, 1], PadMode::Edge).unwrap();
+    ///
+    /// assert_eq!(dest.size(), [3, 3]);
+    /// assert_eq!(dest.as_slice(), &[
+    ///     1, 1, 2,
+    ///     3, 3, 4,
+    ///     3, 3, 4,
+    /// ]);
+    /// ```
+    pub fn pad_into(&self, dest: &mut XDBuf<T, D>, before: [usize; D], after: [usize; D], mode: PadMode<T>) -> Result<(), anyhow::Error> {
+        let mut size = self.size;
+        for a in 0..D {
+            size[a] = size[a]
+                .checked_add(before[a])
+                .and_then(|s| s.checked_add(after[a]))
+                .ok_or_else(|| anyhow!("size is out of range"))?;
+        }
+
+        let fill = match &mode {
+            PadMode::Constant(value) => value.clone(),
+            // バッファは空にならない
+            _ => self.as_slice()[0].clone(),
+        };
+        dest.init(size, fill)?;
+
+        for i in 0..dest.len() {
+            let index = to_mul_dim_index(i, &dest.stride, dest.layout);
+
+            let source = (0..D).try_fold(0, |acc, a| {
+                resolve(index[a] as isize - before[a] as isize, self.size[a], &mode).map(|s| acc + s * self.stride[a])
+            });
+
+            if let Some(source) = source {
+                dest.buf[i] = self.as_slice()[source].clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generate a copy of the buffer with `before` elements removed from the start and `after` elements removed from the end of each axis.
+    ///
+    /// This is the inverse of `pad`. The result has the memory layout of this buffer.
+    ///
+    /// 各軸の先頭から`before`個、末尾から`after`個の要素を取り除いたバッファの複製を生成します。
+    ///
+    /// `pad`の逆の操作です。結果はこのバッファのメモリ配置を持ちます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if no element would remain along an axis.
+    ///
+    /// * いずれかの軸で要素が残らない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{PadMode, XDBuf};
+    ///
+    /// let buf = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// let padded = buf.pad([1, 2], [3, 1], PadMode::Wrap).unwrap();
+    ///
+    /// assert_eq!(padded.crop([1, 2], [3, 1]).unwrap().as_slice(), buf.as_slice());
+    /// assert!(padded.crop([3, 0], [3, 0]).is_err());
+    /// ```
+    pub fn crop(&self, before: [usize; D], after: [usize; D]) -> Result<XDBuf<T, D>, anyhow::Error> {
+        let mut size = self.size;
+        for a in 0..D {
+            size[a] = size[a]
+                .checked_sub(before[a])
+                .and_then(|s| s.checked_sub(after[a]))
+                .filter(|&s| s > 0)
+                .ok_or_else(|| anyhow!("cropped size must not be zero"))?;
+        }
+
+        let stride = XDBuf::<T, D>::calc_dim_stride_with_layout(&size, self.layout)?;
+        let len = XDBuf::<T, D>::calc_total_size(&size)?;
+
+        let buf = (0..len).map(|i| {
+            let index = to_mul_dim_index(i, &stride, self.layout);
+            let source = (0..D).map(|a| (index[a] + before[a]) * self.stride[a]).sum::<usize>();
+            self.as_slice()[source].clone()
+        }).collect();
+
+        Ok(XDBuf {
+            buf,
+            size,
+            stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Resolves the possibly out-of-range `index` along an axis of `size` elements.
+///
+/// Returns `None` if the index refers to `PadMode::Constant`.
+///
+/// 範囲外の可能性がある`index`を、`size`個の要素を持つ軸について解決します。
+///
+/// `PadMode::Constant`を参照する場合は`None`を返します。
+fn resolve<T>(index: isize, size: usize, mode: &PadMode<T>) -> Option<usize> {
+    let last = size as isize - 1;
+
+    match mode {
+        PadMode::Constant(_) => (0..=last).contains(&index).then_some(index as usize),
+        PadMode::Edge => Some(index.clamp(0, last) as usize),
+        PadMode::Wrap => Some(index.rem_euclid(last + 1) as usize),
+        PadMode::Reflect if last == 0 => Some(0),
+        PadMode::Reflect => {
+            let m = index.rem_euclid(2 * last);
+            Some(if m > last { 2 * last - m } else { m } as usize)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn reflect_repeats_beyond_one_period() {
+        let buf = XDBuf::<u8, 1>::new_with_vec([3], vec![1, 2, 3]).unwrap();
+        let padded = buf.pad([5], [5], PadMode::Reflect).unwrap();
+
+        assert_eq!(padded.as_slice(), &[2, 1, 2, 3, 2, 1, 2, 3, 2, 1, 2, 3, 2]);
+
+        let single = XDBuf::<u8, 1>::new([1], 7).unwrap();
+        assert_eq!(single.pad([2], [1], PadMode::Reflect).unwrap().as_slice(), &[7, 7, 7, 7]);
+    }
+
+    #[test]
+    fn pad_keeps_layout_and_crop_inverts() {
+        let buf = XDBuf::<u16, 3>::new_with_layout([2, 3, 4], 0, Layout::LastMajor).unwrap()
+            .map_indexed(|[x, y, z], _| (x + 10 * y + 100 * z) as u16);
+
+        for mode in [PadMode::Constant(9), PadMode::Edge, PadMode::Reflect, PadMode::Wrap] {
+            let padded = buf.pad([1, 0, 2], [0, 3, 1], mode).unwrap();
+
+            assert_eq!(padded.size(), [3, 6, 7]);
+            assert_eq!(padded.layout(), Layout::LastMajor);
+            assert_eq!(padded.get_m([1, 2, 2]), buf.get_m([0, 2, 0]));
+
+            let cropped = padded.crop([1, 0, 2], [0, 3, 1]).unwrap();
+            assert_eq!(cropped.as_slice(), buf.as_slice());
+        }
+    }
+
+    #[test]
+    fn constant_pad_in_2d() {
+        let buf = XDBuf::<i8, 2>::new([1, 1], 1).unwrap();
+        let padded = buf.pad([1, 1], [1, 1], PadMode::Constant(0)).unwrap();
+
+        assert_eq!(padded.as_slice(), &[0, 0, 0, 0, 1, 0, 0, 0, 0]);
+        assert!(buf.pad([usize::MAX, 0], [1, 0], PadMode::Edge).is_err());
+    }
+}
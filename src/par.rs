@@ -0,0 +1,340 @@
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+use anyhow::anyhow;
+use rayon::iter::Either;
+use rayon::prelude::*;
+
+use crate::storage::{Storage, StorageMut};
+use crate::xdbuf::to_mul_dim_index;
+use crate::XDBuf;
+
+/// A mutable view of the elements lined up along one axis of an `XDBuf`.
+///
+/// Obtained from [`XDBuf::par_lanes_mut`].
+///
+/// `XDBuf`の1つの軸に沿って並ぶ要素への可変なビューです。
+///
+/// [`XDBuf::par_lanes_mut`]から取得します。
+#[derive(Debug)]
+pub struct LaneMut<'a, T> {
+    ptr: *mut T,
+    len: usize,
+    stride: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+// `LaneMut`は他のレーンと要素を共有しない`&mut [T]`と同等に扱える
+unsafe impl<T: Send> Send for LaneMut<'_, T> {}
+unsafe impl<T: Sync> Sync for LaneMut<'_, T> {}
+
+impl<'a, T> LaneMut<'a, T> {
+    /// Returns the number of elements in the lane.
+    ///
+    /// レーンの要素数を返します。
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the lane contains no elements.
+    ///
+    /// レーンが要素を持たない場合に`true`を返します。
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get a reference to the `index`-th element of the lane.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// レーンの`index`番目の要素の参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        // 範囲チェック済みなので、レーンが借用している要素を指す
+        Some(unsafe { &*self.ptr.add(index * self.stride) })
+    }
+
+    /// Get a variable reference to the `index`-th element of the lane.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// レーンの`index`番目の要素の可変参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        // 範囲チェック済みなので、レーンが借用している要素を指す
+        Some(unsafe { &mut *self.ptr.add(index * self.stride) })
+    }
+
+    /// Returns an iterator over the elements of the lane.
+    ///
+    /// レーンの要素を走査するイテレータを返します。
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.len).map(move |i| &self[i])
+    }
+
+    /// Returns an iterator that allows modifying each element of the lane.
+    ///
+    /// レーンの要素を変更しながら走査するイテレータを返します。
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        let (ptr, stride) = (self.ptr, self.stride);
+        // 各要素は互いに重ならないので、同時に可変参照を返しても問題ない
+        (0..self.len).map(move |i| unsafe { &mut *ptr.add(i * stride) })
+    }
+}
+
+impl<T> Index<usize> for LaneMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index is out of range")
+    }
+}
+
+impl<T> IndexMut<usize> for LaneMut<'_, T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("index is out of range")
+    }
+}
+
+/// A pointer to the head of a buffer that can be shared between threads.
+///
+/// スレッド間で共有できるバッファ先頭へのポインタです。
+struct SharedPtr<T>(*mut T);
+
+impl<T> Clone for SharedPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SharedPtr<T> {}
+
+unsafe impl<T: Send> Send for SharedPtr<T> {}
+unsafe impl<T: Send> Sync for SharedPtr<T> {}
+
+impl<T, const D: usize, S> XDBuf<T, D, S> {
+    /// Returns a parallel iterator over the elements of the buffer.
+    ///
+    /// バッファの要素を並列に走査するイテレータを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rayon::prelude::*;
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 3>::new([3, 4, 5], 1).unwrap();
+    /// assert_eq!(buf.par_iter().sum::<i32>(), 60);
+    /// ```
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = &T>
+    where
+        T: Sync,
+        S: Storage<T>,
+    {
+        self.as_slice().par_iter()
+    }
+
+    /// Returns a parallel iterator that allows modifying each element of the buffer.
+    ///
+    /// バッファの要素を変更しながら並列に走査するイテレータを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rayon::prelude::*;
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<i32, 3>::new([3, 4, 5], 1).unwrap();
+    /// buf.par_iter_mut().for_each(|v| *v *= 2);
+    ///
+    /// assert_eq!(buf.get(59), Some(&2));
+    /// ```
+    pub fn par_iter_mut(&mut self) -> impl IndexedParallelIterator<Item = &mut T>
+    where
+        T: Send,
+        S: StorageMut<T>,
+    {
+        self.as_mut_slice().par_iter_mut()
+    }
+
+    /// Returns a parallel iterator over the elements of the buffer together with their indices in array notation.
+    ///
+    /// バッファの要素を配列表記のインデックスと共に並列に走査するイテレータを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rayon::prelude::*;
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<usize, 2>::new_with_vec([3, 2], (0..6).collect()).unwrap();
+    /// let items = buf.par_indexed_iter().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(items[4], ([1, 1], &4));
+    /// ```
+    pub fn par_indexed_iter(&self) -> impl IndexedParallelIterator<Item = ([usize; D], &T)>
+    where
+        T: Sync,
+        S: Storage<T>,
+    {
+        let (stride, layout) = (self.stride, self.layout);
+
+        self.as_slice().par_iter().enumerate().map(move |(i, v)| (to_mul_dim_index(i, &stride, layout), v))
+    }
+
+    /// Returns a parallel iterator over every lane along `axis`.
+    ///
+    /// A lane is the sequence of elements whose indices differ only in `axis`.
+    /// Work is split along the slowest-varying axis, so unless `axis` is that axis, each task only touches a contiguous part of the buffer.
+    ///
+    /// `axis`に沿ったすべてのレーンを並列に走査するイテレータを返します。
+    ///
+    /// レーンとは、`axis`のインデックスのみが異なる要素の並びです。
+    /// 処理は最も変化の遅い軸に沿って分割されるため、`axis`がその軸でない限り、各タスクはバッファの連続した領域のみに触れます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rayon::prelude::*;
+    /// use xdbuf::XDBuf;
+    ///
+    /// // [0, 1, 2,
+    /// //  3, 4, 5]
+    /// let mut buf = XDBuf::<i32, 2>::new_with_vec([3, 2], (0..6).collect()).unwrap();
+    ///
+    /// // 各列の累積和
+    /// buf.par_lanes_mut(1).unwrap().for_each(|mut lane| {
+    ///     let mut acc = 0;
+    ///     lane.iter_mut().for_each(|v| {
+    ///         acc += *v;
+    ///         *v = acc;
+    ///     });
+    /// });
+    ///
+    /// assert_eq!(buf.get(3), Some(&3));
+    /// assert_eq!(buf.get(5), Some(&7));
+    /// ```
+    pub fn par_lanes_mut(&mut self, axis: usize) -> Result<impl ParallelIterator<Item = LaneMut<'_, T>>, anyhow::Error>
+    where
+        T: Send,
+        S: StorageMut<T>,
+    {
+        if axis >= D {
+            return Err(anyhow!("axis is out of range"));
+        }
+
+        let len = self.size[axis];
+        let stride = self.stride[axis];
+        let slowest_axis = self.layout.slowest_axis::<D>();
+        let slab_len = self.stride[slowest_axis];
+
+        if axis == slowest_axis {
+            // 最も遅い軸に沿ったレーンはスライスを跨ぐため、レーンの先頭位置ごとに分割する
+            let ptr = SharedPtr(self.as_mut_slice().as_mut_ptr());
+
+            return Ok(Either::Left((0..slab_len).into_par_iter().map(move |start| {
+                // フィールド単位ではなく`SharedPtr`ごとキャプチャさせる
+                let ptr = ptr;
+                LaneMut {
+                    // `start < slab_len`なので、レーンの先頭はバッファ内を指す
+                    ptr: unsafe { ptr.0.add(start) },
+                    len,
+                    stride,
+                    _marker: PhantomData,
+                }
+            })));
+        }
+
+        let lane_span = stride * len;
+
+        Ok(Either::Right(self.as_mut_slice().par_chunks_mut(slab_len).flat_map_iter(move |slab| {
+            let ptr = slab.as_mut_ptr();
+
+            (0..slab.len()).step_by(lane_span)
+                .flat_map(move |block| block..block + stride)
+                .map(move |start| LaneMut {
+                    // `start < slab.len()`なので、レーンの先頭はスライス内を指す
+                    ptr: unsafe { ptr.add(start) },
+                    len,
+                    stride,
+                    _marker: PhantomData,
+                })
+        })))
+    }
+
+    /// Generate a new `XDBuf` by applying `f` to each element in parallel.
+    ///
+    /// 各要素に`f`を並列に適用して新しい`XDBuf`を生成します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 2>::new([3, 2], 2).unwrap();
+    /// let mapped = buf.par_map(|&v| v as f32 * 0.5);
+    ///
+    /// assert_eq!(mapped.size(), [3, 2]);
+    /// assert_eq!(mapped.get(0), Some(&1.0));
+    /// ```
+    pub fn par_map<U: Send>(&self, f: impl Fn(&T) -> U + Sync + Send) -> XDBuf<U, D>
+    where
+        T: Sync,
+        S: Storage<T>,
+    {
+        XDBuf {
+            buf: self.as_slice().par_iter().map(f).collect(),
+            size: self.size,
+            stride: self.stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn par_lanes_mut_visits_every_lane_along_each_axis() {
+        let size = [3, 4, 5];
+
+        for axis in 0..3 {
+            let mut buf = XDBuf::<usize, 3>::new(size, 0).unwrap();
+            let lanes = buf.par_lanes_mut(axis).unwrap().map(|mut lane| {
+                lane.iter_mut().enumerate().for_each(|(i, v)| *v = i);
+                lane.len()
+            }).collect::<Vec<_>>();
+
+            assert_eq!(lanes.len(), 60 / size[axis]);
+            assert!(lanes.iter().all(|&len| len == size[axis]));
+
+            for i in buf.idx_range() {
+                assert_eq!(buf.get(i), Some(&buf.to_mul_dim_index(i)[axis]));
+            }
+        }
+    }
+
+    #[test]
+    fn par_lanes_mut_rejects_invalid_axis() {
+        let mut buf = XDBuf::<usize, 2>::new([3, 4], 0).unwrap();
+        assert!(buf.par_lanes_mut(2).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+use std::mem::size_of;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::XDBuf;
+
+/// Structure that keeps idle `XDBuf`s and lends them out for jobs of varying sizes
+///
+/// The pool can be shared between threads. A borrowed buffer returns to the pool when its guard is dropped.
+///
+/// 使用されていない`XDBuf`を保持し、サイズの異なる処理に貸し出す構造体です。
+///
+/// プールはスレッド間で共有できます。貸し出されたバッファはガードが破棄されるとプールに戻ります。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::XDBufPool;
+///
+/// let pool = XDBufPool::<f32, 2>::new();
+///
+/// std::thread::scope(|s| {
+///     for i in 1..=4 {
+///         let pool = &pool;
+///         s.spawn(move || {
+///             let mut buf = pool.acquire([16 * i, 16], 0.0).unwrap();
+///             buf.set_m([0, 0], 1.0).unwrap();
+///         });
+///     }
+/// });
+///
+/// assert!(pool.idle_len() >= 1);
+/// ```
+#[derive(Debug)]
+pub struct XDBufPool<T, const D: usize> {
+    idle: Mutex<Idle<T, D>>,
+    max_retained_bytes: usize,
+}
+
+#[derive(Debug)]
+struct Idle<T, const D: usize> {
+    bufs: Vec<XDBuf<T, D>>,
+    bytes: usize,
+}
+
+impl<T, const D: usize> XDBufPool<T, D> {
+    /// Generate a new, empty `XDBufPool` that retains any number of idle buffers.
+    ///
+    /// 使用されていないバッファを無制限に保持する新しい空の`XDBufPool`を生成します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufPool;
+    ///
+    /// let pool = XDBufPool::<u8, 3>::new();
+    /// assert_eq!(pool.idle_len(), 0);
+    /// ```
+    pub fn new() -> Self {
+        Self::with_max_retained_bytes(usize::MAX)
+    }
+
+    /// Generate a new, empty `XDBufPool` that retains idle buffers up to `max_retained_bytes` of capacity in total.
+    ///
+    /// A returned buffer that would exceed the limit is freed instead of being kept.
+    ///
+    /// 使用されていないバッファを容量の合計が`max_retained_bytes`になるまで保持する、新しい空の`XDBufPool`を生成します。
+    ///
+    /// 上限を超えることになる返却されたバッファは、保持されずに解放されます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufPool;
+    ///
+    /// let pool = XDBufPool::<u32, 2>::with_max_retained_bytes(1024);
+    ///
+    /// drop(pool.acquire([8, 8], 0).unwrap()); // 256バイト
+    /// assert_eq!(pool.idle_len(), 1);
+    ///
+    /// drop(pool.acquire([32, 32], 0).unwrap()); // 4096バイト
+    /// assert_eq!(pool.idle_len(), 1);
+    /// ```
+    pub fn with_max_retained_bytes(max_retained_bytes: usize) -> Self {
+        Self {
+            idle: Mutex::new(Idle {
+                bufs: Vec::new(),
+                bytes: 0,
+            }),
+            max_retained_bytes,
+        }
+    }
+
+    /// Borrows a buffer initialized to `size` with `fill`.
+    ///
+    /// The idle buffer with the smallest capacity that can hold `size` is reused.
+    /// If there is none, a new buffer is allocated.
+    ///
+    /// `size`と`fill`で初期化されたバッファを借ります。
+    ///
+    /// `size`を保持できる使用されていないバッファのうち、容量が最も小さいものが再利用されます。
+    /// 存在しない場合は新しいバッファが割り当てられます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufPool;
+    ///
+    /// let pool = XDBufPool::<i32, 2>::new();
+    ///
+    /// let ptr = {
+    ///     let buf = pool.acquire([64, 64], 0).unwrap();
+    ///     buf.as_slice().as_ptr()
+    /// };
+    ///
+    /// // 返却された割り当てが再利用される
+    /// let buf = pool.acquire([32, 16], 7).unwrap();
+    /// assert_eq!(buf.as_slice().as_ptr(), ptr);
+    /// assert_eq!(buf.get_m([31, 15]), Some(&7));
+    /// ```
+    pub fn acquire(&self, size: [usize; D], fill: T) -> Result<PooledXDBuf<'_, T, D>, anyhow::Error>
+    where
+        T: Clone,
+    {
+        let len = XDBuf::<T, D>::calc_total_size(&size)?;
+
+        let reused = {
+            let mut idle = self.lock();
+
+            let best = idle.bufs.iter().enumerate()
+                .filter(|(_, buf)| buf.capacity() >= len)
+                .min_by_key(|(_, buf)| buf.capacity())
+                .map(|(i, _)| i);
+
+            best.map(|i| {
+                let buf = idle.bufs.swap_remove(i);
+                idle.bytes -= Self::bytes_of(&buf);
+                buf
+            })
+        };
+
+        let buf = match reused {
+            Some(mut buf) => {
+                buf.init(size, fill)?;
+                buf
+            }
+            None => XDBuf::new(size, fill)?,
+        };
+
+        Ok(PooledXDBuf {
+            pool: self,
+            buf: Some(buf),
+        })
+    }
+
+    /// Returns the number of idle buffers held by the pool.
+    ///
+    /// プールが保持している使用されていないバッファの数を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufPool;
+    ///
+    /// let pool = XDBufPool::<i32, 2>::new();
+    ///
+    /// let a = pool.acquire([4, 4], 0).unwrap();
+    /// let b = pool.acquire([4, 4], 0).unwrap();
+    /// assert_eq!(pool.idle_len(), 0);
+    ///
+    /// drop(a);
+    /// drop(b);
+    /// assert_eq!(pool.idle_len(), 2);
+    /// ```
+    pub fn idle_len(&self) -> usize {
+        self.lock().bufs.len()
+    }
+
+    /// Returns the total capacity in bytes of the idle buffers held by the pool.
+    ///
+    /// プールが保持している使用されていないバッファの容量の合計をバイト単位で返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufPool;
+    ///
+    /// let pool = XDBufPool::<u16, 2>::new();
+    ///
+    /// drop(pool.acquire([4, 4], 0).unwrap());
+    /// assert!(pool.retained_bytes() >= 32);
+    /// ```
+    pub fn retained_bytes(&self) -> usize {
+        self.lock().bytes
+    }
+
+    /// Reduce the capacity of each idle buffer as much as possible.
+    ///
+    /// 使用されていない各バッファの容量をできるだけ縮小します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufPool;
+    ///
+    /// let pool = XDBufPool::<u8, 2>::new();
+    ///
+    /// {
+    ///     let mut buf = pool.acquire([100, 100], 0).unwrap();
+    ///     buf.init([10, 10], 0).unwrap();
+    /// }
+    ///
+    /// pool.shrink_to_fit();
+    /// assert_eq!(pool.retained_bytes(), 100);
+    /// ```
+    pub fn shrink_to_fit(&self) {
+        let mut idle = self.lock();
+
+        idle.bufs.iter_mut().for_each(|buf| buf.shrink_to_fit());
+        idle.bytes = idle.bufs.iter().map(Self::bytes_of).sum();
+    }
+
+    /// Frees all idle buffers held by the pool.
+    ///
+    /// プールが保持している使用されていないバッファをすべて解放します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufPool;
+    ///
+    /// let pool = XDBufPool::<u8, 2>::new();
+    ///
+    /// drop(pool.acquire([4, 4], 0).unwrap());
+    /// pool.clear();
+    /// assert_eq!(pool.idle_len(), 0);
+    /// assert_eq!(pool.retained_bytes(), 0);
+    /// ```
+    pub fn clear(&self) {
+        let mut idle = self.lock();
+
+        idle.bufs.clear();
+        idle.bytes = 0;
+    }
+
+    fn release(&self, buf: XDBuf<T, D>) {
+        let bytes = Self::bytes_of(&buf);
+        let mut idle = self.lock();
+
+        if idle.bytes.saturating_add(bytes) <= self.max_retained_bytes {
+            idle.bytes += bytes;
+            idle.bufs.push(buf);
+        }
+    }
+
+    fn bytes_of(buf: &XDBuf<T, D>) -> usize {
+        buf.capacity() * size_of::<T>()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Idle<T, D>> {
+        // 保持しているバッファはロック中にパニックしても整合性を失わない
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T, const D: usize> Default for XDBufPool<T, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Guard that lends an `XDBuf` from an `XDBufPool` and returns it to the pool when dropped
+///
+/// `XDBufPool`から`XDBuf`を貸し出し、破棄されるとプールに戻すガードです。
+#[derive(Debug)]
+pub struct PooledXDBuf<'a, T, const D: usize> {
+    pool: &'a XDBufPool<T, D>,
+    buf: Option<XDBuf<T, D>>,
+}
+
+impl<T, const D: usize> PooledXDBuf<'_, T, D> {
+    /// Takes the buffer out of the guard so that it is not returned to the pool.
+    ///
+    /// バッファがプールに戻らないようにガードから取り出します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBufPool;
+    ///
+    /// let pool = XDBufPool::<i32, 2>::new();
+    ///
+    /// let buf = pool.acquire([2, 2], 1).unwrap().into_inner();
+    /// assert_eq!(buf.as_slice(), &[1; 4]);
+    /// assert_eq!(pool.idle_len(), 0);
+    /// ```
+    pub fn into_inner(mut self) -> XDBuf<T, D> {
+        // `buf`は破棄されるまで常に`Some`
+        self.buf.take().unwrap()
+    }
+}
+
+impl<T, const D: usize> Deref for PooledXDBuf<'_, T, D> {
+    type Target = XDBuf<T, D>;
+
+    fn deref(&self) -> &Self::Target {
+        // `buf`は破棄されるまで常に`Some`
+        self.buf.as_ref().unwrap()
+    }
+}
+
+impl<T, const D: usize> DerefMut for PooledXDBuf<'_, T, D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // `buf`は破棄されるまで常に`Some`
+        self.buf.as_mut().unwrap()
+    }
+}
+
+impl<T, const D: usize> Drop for PooledXDBuf<'_, T, D> {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            self.pool.release(buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn acquire_picks_smallest_fitting_capacity() {
+        let pool = XDBufPool::<u8, 1>::new();
+
+        let small = pool.acquire([10], 0).unwrap();
+        let medium = pool.acquire([100], 0).unwrap();
+        let large = pool.acquire([1000], 0).unwrap();
+        let medium_ptr = medium.as_slice().as_ptr();
+        drop((small, medium, large));
+
+        let buf = pool.acquire([50], 1).unwrap();
+        assert_eq!(buf.as_slice().as_ptr(), medium_ptr);
+        assert_eq!(buf.len(), 50);
+        assert!(buf.iter().all(|&v| v == 1));
+        assert_eq!(pool.idle_len(), 2);
+    }
+
+    #[test]
+    fn retained_bytes_never_exceed_limit() {
+        let pool = XDBufPool::<u64, 1>::with_max_retained_bytes(100 * 8);
+
+        let bufs = (0..5).map(|_| pool.acquire([30], 0).unwrap()).collect::<Vec<_>>();
+        drop(bufs);
+
+        assert!(pool.retained_bytes() <= 100 * 8);
+        assert_eq!(pool.idle_len(), pool.retained_bytes() / (30 * 8));
+    }
+}
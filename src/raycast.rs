@@ -0,0 +1,276 @@
+use anyhow::anyhow;
+
+use crate::storage::Storage;
+use crate::XDBuf;
+
+/// Iterator over the elements pierced by a ray, traversed with the Amanatides-Woo algorithm
+///
+/// Each item is `(scalar_index, entry_t, entered_face_axis)`.
+/// `entered_face_axis` is the axis perpendicular to the face through which the ray entered the element,
+/// or `None` for the element containing the origin of the ray.
+///
+/// Amanatides-Wooのアルゴリズムで走査した、光線が貫く要素のイテレータです。
+///
+/// 各要素は`(スカラーのインデックス, 進入時のt, 進入した面の軸)`です。
+/// 進入した面の軸は光線が要素に進入した面に垂直な軸で、光線の原点を含む要素では`None`になります。
+#[derive(Debug, Clone)]
+pub struct Raycast<const D: usize> {
+    size: [usize; D],
+    stride: [usize; D],
+    current: [usize; D],
+    forward: [bool; D],
+    t_max: [f64; D],
+    t_delta: [f64; D],
+    t: f64,
+    axis: Option<usize>,
+    max_t: f64,
+    done: bool,
+}
+
+impl<const D: usize> Iterator for Raycast<D> {
+    type Item = (usize, f64, Option<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let scalar = self.current.iter().zip(self.stride).map(|(&i, s)| i * s).sum();
+        let item = (scalar, self.t, self.axis);
+
+        // 次に交差する境界の軸
+        let next = (0..D).min_by(|&a, &b| self.t_max[a].total_cmp(&self.t_max[b]));
+
+        match next {
+            Some(a) if self.t_max[a] <= self.max_t => {
+                let moved = if self.forward[a] {
+                    Some(self.current[a] + 1).filter(|&i| i < self.size[a])
+                } else {
+                    self.current[a].checked_sub(1)
+                };
+
+                match moved {
+                    Some(i) => {
+                        self.current[a] = i;
+                        self.t = self.t_max[a];
+                        self.t_max[a] += self.t_delta[a];
+                        self.axis = Some(a);
+                    }
+                    None => self.done = true,
+                }
+            }
+            _ => self.done = true,
+        }
+
+        Some(item)
+    }
+}
+
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Returns an iterator over the elements pierced by the ray `origin + t * dir` for `0 <= t <= max_t`.
+    ///
+    /// The element at index `i` occupies the range from `i` to `i + 1` along each axis.
+    /// A ray starting outside the buffer begins at the point where it enters the buffer, and the traversal ends at the boundary.
+    ///
+    /// `0 <= t <= max_t`の光線`origin + t * dir`が貫く要素を走査するイテレータを返します。
+    ///
+    /// インデックス`i`の要素は各軸に沿って`i`から`i + 1`の範囲を占めます。
+    /// バッファの外側から始まる光線はバッファに進入する点から始まり、走査は境界で終了します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `origin`, `dir` or `max_t` is not finite.
+    ///
+    /// * `origin`、`dir`、`max_t`が有限でない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 3>::new([4, 4, 4], 0).unwrap();
+    ///
+    /// // 外側から+x方向に進む光線
+    /// let hits = buf.raycast([-1.0, 0.5, 2.5], [1.0, 0.0, 0.0], 10.0).unwrap().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(hits.len(), 4);
+    /// assert_eq!(hits[0], (buf.to_scalar_index(&[0, 0, 2]).unwrap(), 1.0, Some(0)));
+    /// assert_eq!(hits[3].1, 4.0);
+    /// ```
+    pub fn raycast(&self, origin: [f64; D], dir: [f64; D], max_t: f64) -> Result<Raycast<D>, anyhow::Error> {
+        if !origin.iter().chain(&dir).chain([&max_t]).all(|v| v.is_finite()) {
+            return Err(anyhow!("origin, dir and max_t must be finite"));
+        }
+
+        let mut raycast = Raycast {
+            size: self.size,
+            stride: self.stride,
+            current: [0; D],
+            forward: dir.map(|d| d >= 0.0),
+            t_max: [f64::INFINITY; D],
+            t_delta: dir.map(|d| 1.0 / d.abs()),
+            t: 0.0,
+            axis: None,
+            max_t,
+            done: false,
+        };
+
+        // 光線がバッファに含まれる区間
+        let mut t_enter = 0.0_f64;
+        let mut t_exit = max_t;
+        let mut t_leave = f64::INFINITY;
+        for a in 0..D {
+            let end = self.size[a] as f64;
+
+            if dir[a] == 0.0 {
+                if origin[a] < 0.0 || origin[a] >= end {
+                    raycast.done = true;
+                }
+                continue;
+            }
+
+            let (t0, t1) = ((0.0 - origin[a]) / dir[a], (end - origin[a]) / dir[a]);
+            let (near, far) = (t0.min(t1), t0.max(t1));
+
+            if near > t_enter {
+                t_enter = near;
+                raycast.axis = Some(a);
+            }
+            t_exit = t_exit.min(far);
+            t_leave = t_leave.min(far);
+        }
+
+        // バッファを出る時刻が進入時刻以前なら、光線は境界に触れるだけで要素を貫かない
+        if raycast.done || t_enter > t_exit || (t_enter == t_exit && raycast.axis.is_some()) || t_leave <= t_enter {
+            raycast.done = true;
+            return Ok(raycast);
+        }
+
+        raycast.t = t_enter;
+        for a in 0..D {
+            let p = origin[a] + dir[a] * t_enter;
+
+            // 面上の点は進行方向の要素に属する
+            let i = if raycast.axis == Some(a) {
+                if dir[a] > 0.0 { 0 } else { self.size[a] - 1 }
+            } else if dir[a] < 0.0 {
+                ((p.ceil() - 1.0).max(0.0) as usize).min(self.size[a] - 1)
+            } else {
+                (p.floor().max(0.0) as usize).min(self.size[a] - 1)
+            };
+            raycast.current[a] = i;
+
+            if dir[a] > 0.0 {
+                raycast.t_max[a] = ((i + 1) as f64 - origin[a]) / dir[a];
+            } else if dir[a] < 0.0 {
+                raycast.t_max[a] = (i as f64 - origin[a]) / dir[a];
+            }
+        }
+
+        Ok(raycast)
+    }
+
+    /// Traverses the elements pierced by the ray and returns the first one that satisfies the condition.
+    ///
+    /// Returns `None` if the ray leaves the buffer or reaches `max_t` without such an element.
+    ///
+    /// 光線が貫く要素を走査し、条件を満たす最初の要素を返します。
+    ///
+    /// そのような要素がないまま光線がバッファを出るか`max_t`に達した場合は`None`を返します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `origin`, `dir` or `max_t` is not finite.
+    ///
+    /// * `origin`、`dir`、`max_t`が有限でない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new([8, 8], 0).unwrap();
+    /// buf.set_m([5, 5], 1).unwrap();
+    ///
+    /// let hit = buf.raycast_until([0.5, 0.5], [1.0, 1.0], 100.0, |&v, _| v == 1).unwrap();
+    /// let (index, t, axis) = hit.unwrap();
+    ///
+    /// assert_eq!(buf.to_mul_dim_index(index), [5, 5]);
+    /// assert_eq!(t, 4.5);
+    /// assert!(axis.is_some());
+    /// ```
+    pub fn raycast_until(
+        &self,
+        origin: [f64; D],
+        dir: [f64; D],
+        max_t: f64,
+        f: impl Fn(&T, usize) -> bool,
+    ) -> Result<Option<(usize, f64, Option<usize>)>, anyhow::Error> {
+        // 走査されるインデックスはすべて範囲内
+        Ok(self.raycast(origin, dir, max_t)?.find(|&(i, _, _)| f(&self.as_slice()[i], i)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn visits_face_connected_voxels_until_boundary() {
+        let buf = XDBuf::<u8, 3>::new([5, 6, 7], 0).unwrap();
+        let hits = buf.raycast([4.2, 0.3, 6.9], [-0.7, 1.1, -0.9], 1000.0).unwrap().collect::<Vec<_>>();
+
+        assert_eq!(hits[0].2, None);
+        for pair in hits.windows(2) {
+            let (a, b) = (buf.to_mul_dim_index(pair[0].0), buf.to_mul_dim_index(pair[1].0));
+            let axis = pair[1].2.unwrap();
+
+            assert_eq!(a.iter().zip(b).map(|(&a, b)| a.abs_diff(b)).sum::<usize>(), 1);
+            assert_ne!(a[axis], b[axis]);
+            assert!(pair[0].1 <= pair[1].1);
+        }
+
+        let last = buf.to_mul_dim_index(hits.last().unwrap().0);
+        assert!(last[0] == 0 || last[1] == 5 || last[2] == 0);
+    }
+
+    #[test]
+    fn stops_at_max_t_and_misses_cleanly() {
+        let buf = XDBuf::<u8, 2>::new([10, 10], 0).unwrap();
+
+        assert_eq!(buf.raycast([0.5, 0.5], [1.0, 0.0], 2.4).unwrap().count(), 3);
+        assert_eq!(buf.raycast([-1.0, 20.0], [1.0, 0.0], 100.0).unwrap().count(), 0);
+        assert_eq!(buf.raycast([-1.0, 0.5], [-1.0, 0.0], 100.0).unwrap().count(), 0);
+        assert_eq!(buf.raycast([3.5, 3.5], [0.0, 0.0], 100.0).unwrap().count(), 1);
+        assert!(buf.raycast([f64::NAN, 0.0], [1.0, 0.0], 1.0).is_err());
+    }
+
+    #[test]
+    fn enters_from_the_far_side() {
+        let buf = XDBuf::<u8, 2>::new([4, 4], 0).unwrap();
+        let hits = buf.raycast([10.0, 1.5], [-2.0, 0.0], 100.0).unwrap().collect::<Vec<_>>();
+
+        let indices = hits.iter().map(|&(i, _, _)| buf.to_mul_dim_index(i)).collect::<Vec<_>>();
+        assert_eq!(indices, vec![[3, 1], [2, 1], [1, 1], [0, 1]]);
+        assert_eq!(hits[0].1, 3.0);
+        assert_eq!(hits[0].2, Some(0));
+    }
+
+    #[test]
+    fn origin_on_a_face_starts_in_the_pierced_element() {
+        let buf = XDBuf::<u8, 2>::new([4, 4], 0).unwrap();
+
+        let hits = buf.raycast([2.0, 0.5], [-1.0, 0.0], 100.0).unwrap().collect::<Vec<_>>();
+        let indices = hits.iter().map(|&(i, _, _)| buf.to_mul_dim_index(i)).collect::<Vec<_>>();
+        assert_eq!(indices, vec![[1, 0], [0, 0]]);
+        assert_eq!((hits[0].1, hits[1].1), (0.0, 1.0));
+
+        let hits = buf.raycast([1.5, 3.0], [1.0, -1.0], 100.0).unwrap().collect::<Vec<_>>();
+        assert_eq!(buf.to_mul_dim_index(hits[0].0), [1, 2]);
+
+        // 境界に触れるだけの光線は要素を貫かない
+        assert_eq!(buf.raycast([0.0, 1.5], [-1.0, 0.0], 100.0).unwrap().count(), 0);
+        assert_eq!(buf.raycast([4.0, 1.5], [1.0, 0.5], 100.0).unwrap().count(), 0);
+        assert_eq!(buf.raycast([-1.0, 3.0], [1.0, 1.0], 100.0).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,383 @@
+use std::marker::PhantomData;
+use std::ops::Div;
+
+use anyhow::anyhow;
+use num_traits::{FromPrimitive, Zero};
+
+use crate::storage::Storage;
+use crate::xdbuf::to_mul_dim_index;
+use crate::XDBuf;
+
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Folds the elements along `axis` with `f`, producing a buffer with one dimension less.
+    ///
+    /// The dimension `E` of the result must be `D - 1`, and is usually inferred from the type of the result.
+    /// The result has the memory layout of this buffer.
+    ///
+    /// `axis`に沿った要素を`f`で畳み込み、次元が1つ少ないバッファを生成します。
+    ///
+    /// 結果の次元`E`は`D - 1`である必要があり、通常は結果の型から推論されます。
+    /// 結果はこのバッファのメモリ配置を持ちます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    /// * Error if `E` is not `D - 1`.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    /// * `E`が`D - 1`でない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// // [1, 2, 3,
+    /// //  4, 5, 6]
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([3, 2], (1..=6).collect()).unwrap();
+    ///
+    /// // 各行の要素を文字列として連結
+    /// let joined: XDBuf<String, 1> = buf.fold_axis(0, String::new(), |acc, v| acc + &v.to_string()).unwrap();
+    ///
+    /// assert_eq!(joined.as_slice(), &["123".to_string(), "456".to_string()]);
+    /// ```
+    pub fn fold_axis<'a, const E: usize, U: Clone>(
+        &'a self,
+        axis: usize,
+        init: U,
+        mut f: impl FnMut(U, &'a T) -> U,
+    ) -> Result<XDBuf<U, E>, anyhow::Error> {
+        if axis >= D {
+            return Err(anyhow!("axis is out of range"));
+        }
+
+        if E + 1 != D {
+            return Err(anyhow!("dimension of the result must be {}, but is {}", D - 1, E));
+        }
+
+        let mut size = [0; E];
+        let mut stride = [0; E];
+        for (i, j) in (0..D).filter(|&j| j != axis).enumerate() {
+            size[i] = self.size[j];
+            stride[i] = self.stride[j];
+        }
+
+        // 次元を1つ除いても配置の順序は変わらない
+        let out_stride = XDBuf::<U, E>::calc_dim_stride_with_layout(&size, self.layout)?;
+        let out_len = XDBuf::<U, E>::calc_total_size(&size)?;
+
+        let buf = (0..out_len).map(|o| {
+            let index = to_mul_dim_index(o, &out_stride, self.layout);
+            let base = index.iter().zip(stride).map(|(&i, s)| i * s).sum::<usize>();
+
+            (0..self.size[axis]).fold(init.clone(), |acc, k| {
+                f(acc, &self.as_slice()[base + k * self.stride[axis]])
+            })
+        }).collect();
+
+        Ok(XDBuf {
+            buf,
+            size,
+            stride: out_stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Computes the sum of the elements along `axis`.
+    ///
+    /// `axis`に沿った要素の和を計算します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    /// * Error if `E` is not `D - 1`.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    /// * `E`が`D - 1`でない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// // [1, 2, 3,
+    /// //  4, 5, 6]
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([3, 2], (1..=6).collect()).unwrap();
+    ///
+    /// let column_sum: XDBuf<i32, 1> = buf.sum_axis(1).unwrap();
+    /// assert_eq!(column_sum.as_slice(), &[5, 7, 9]);
+    /// ```
+    pub fn sum_axis<const E: usize>(&self, axis: usize) -> Result<XDBuf<T, E>, anyhow::Error>
+    where
+        T: Clone + Zero,
+    {
+        self.fold_axis(axis, T::zero(), |acc, v| acc + v.clone())
+    }
+
+    /// Computes the arithmetic mean of the elements along `axis`.
+    ///
+    /// `axis`に沿った要素の算術平均を計算します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    /// * Error if `E` is not `D - 1`.
+    /// * Error if the number of elements along `axis` cannot be represented by `T`.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    /// * `E`が`D - 1`でない場合エラーになります。
+    /// * `axis`に沿った要素数を`T`で表現できない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<f32, 2>::new_with_vec([3, 2], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    ///
+    /// let row_mean: XDBuf<f32, 1> = buf.mean_axis(0).unwrap();
+    /// assert_eq!(row_mean.as_slice(), &[2.0, 5.0]);
+    /// ```
+    pub fn mean_axis<const E: usize>(&self, axis: usize) -> Result<XDBuf<T, E>, anyhow::Error>
+    where
+        T: Clone + Zero + Div<Output = T> + FromPrimitive,
+    {
+        let mut mean = self.sum_axis::<E>(axis)?;
+        let count = T::from_usize(self.size[axis]).ok_or(
+            anyhow!("number of elements cannot be represented by the element type")
+        )?;
+
+        mean.buf.iter_mut().for_each(|v| *v = v.clone() / count.clone());
+
+        Ok(mean)
+    }
+
+    /// Computes the minimum of the elements along `axis`.
+    ///
+    /// Elements that cannot be compared, such as NaN, are skipped unless all elements are such.
+    ///
+    /// `axis`に沿った要素の最小値を計算します。
+    ///
+    /// NaNなどの比較できない要素は、すべての要素がそうでない限り読み飛ばされます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    /// * Error if `E` is not `D - 1`.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    /// * `E`が`D - 1`でない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([3, 2], vec![3, 1, 2, 4, 6, 5]).unwrap();
+    ///
+    /// let min: XDBuf<i32, 1> = buf.min_axis(0).unwrap();
+    /// assert_eq!(min.as_slice(), &[1, 4]);
+    /// ```
+    pub fn min_axis<const E: usize>(&self, axis: usize) -> Result<XDBuf<T, E>, anyhow::Error>
+    where
+        T: Clone + PartialOrd,
+    {
+        self.select_axis(axis, |v, acc| v < acc)
+    }
+
+    /// Computes the maximum of the elements along `axis`.
+    ///
+    /// Elements that cannot be compared, such as NaN, are skipped unless all elements are such.
+    ///
+    /// `axis`に沿った要素の最大値を計算します。
+    ///
+    /// NaNなどの比較できない要素は、すべての要素がそうでない限り読み飛ばされます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    /// * Error if `E` is not `D - 1`.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    /// * `E`が`D - 1`でない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// // 最大値投影
+    /// let volume = XDBuf::<u8, 3>::new_with_vec([2, 2, 2], vec![1, 8, 3, 4, 5, 6, 7, 2]).unwrap();
+    ///
+    /// let mip: XDBuf<u8, 2> = volume.max_axis(2).unwrap();
+    /// assert_eq!(mip.as_slice(), &[5, 8, 7, 4]);
+    /// ```
+    pub fn max_axis<const E: usize>(&self, axis: usize) -> Result<XDBuf<T, E>, anyhow::Error>
+    where
+        T: Clone + PartialOrd,
+    {
+        self.select_axis(axis, |v, acc| v > acc)
+    }
+
+    /// Selects along `axis` the element for which `better(element, current)` holds against all others.
+    ///
+    /// `axis`に沿って、`better(要素, 現在の値)`が他のすべてに対して成り立つ要素を選択します。
+    fn select_axis<const E: usize>(&self, axis: usize, better: impl Fn(&T, &T) -> bool) -> Result<XDBuf<T, E>, anyhow::Error>
+    where
+        T: Clone + PartialOrd,
+    {
+        let selected = self.fold_axis::<E, Option<&T>>(axis, None, |acc, v| match acc {
+            Some(acc) if !better(v, acc) && !is_incomparable(acc) => Some(acc),
+            _ => Some(v),
+        })?;
+
+        // 軸に沿った要素は少なくとも1つ存在するので、必ず`Some`になる
+        Ok(XDBuf {
+            buf: selected.buf.into_iter().map(|v| v.unwrap().clone()).collect(),
+            size: selected.size,
+            stride: selected.stride,
+            layout: selected.layout,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the index in array notation of the first maximum element.
+    ///
+    /// Elements that cannot be compared, such as NaN, are skipped.
+    /// Returns `None` if no element can be compared.
+    ///
+    /// 最初の最大の要素の配列表記のインデックスを返します。
+    ///
+    /// NaNなどの比較できない要素は読み飛ばされます。
+    /// 比較できる要素がない場合は`None`を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<f32, 2>::new_with_vec([2, 2], vec![1.0, f32::NAN, 4.0, 2.0]).unwrap();
+    /// assert_eq!(buf.argmax(), Some([0, 1]));
+    /// ```
+    pub fn argmax(&self) -> Option<[usize; D]>
+    where
+        T: PartialOrd,
+    {
+        self.arg_select(|v, acc| v > acc)
+    }
+
+    /// Returns the index in array notation of the first minimum element.
+    ///
+    /// Elements that cannot be compared, such as NaN, are skipped.
+    /// Returns `None` if no element can be compared.
+    ///
+    /// 最初の最小の要素の配列表記のインデックスを返します。
+    ///
+    /// NaNなどの比較できない要素は読み飛ばされます。
+    /// 比較できる要素がない場合は`None`を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![3, 1, 4, 1]).unwrap();
+    /// assert_eq!(buf.argmin(), Some([1, 0]));
+    /// ```
+    pub fn argmin(&self) -> Option<[usize; D]>
+    where
+        T: PartialOrd,
+    {
+        self.arg_select(|v, acc| v < acc)
+    }
+
+    /// Returns the index in array notation of the first element for which `better(element, current)` holds against all others.
+    ///
+    /// `better(要素, 現在の値)`が他のすべてに対して成り立つ最初の要素の配列表記のインデックスを返します。
+    fn arg_select(&self, better: impl Fn(&T, &T) -> bool) -> Option<[usize; D]>
+    where
+        T: PartialOrd,
+    {
+        self.as_slice().iter().enumerate()
+            .filter(|(_, v)| !is_incomparable(*v))
+            .fold(None, |acc: Option<(usize, &T)>, (i, v)| match acc {
+                Some((_, a)) if !better(v, a) => acc,
+                _ => Some((i, v)),
+            })
+            .map(|(i, _)| self.to_mul_dim_index(i))
+    }
+}
+
+/// Returns `true` if `v` cannot be compared even with itself, such as NaN.
+///
+/// NaNのように、`v`が自身とも比較できない場合に`true`を返します。
+#[allow(clippy::eq_op)]
+fn is_incomparable<T: PartialOrd>(v: &T) -> bool {
+    v.partial_cmp(v).is_none()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn reduces_every_axis_of_a_volume() {
+        let buf = XDBuf::<i64, 3>::new_with_vec([2, 3, 4], (0..24).collect()).unwrap();
+
+        for axis in 0..3 {
+            let sum: XDBuf<i64, 2> = buf.sum_axis(axis).unwrap();
+
+            for (index, v) in sum.indexed_iter() {
+                let mut expected = 0;
+                for k in 0..buf.size()[axis] {
+                    let mut full = [0; 3];
+                    let mut rest = index.iter();
+                    for (j, f) in full.iter_mut().enumerate() {
+                        *f = if j == axis { k } else { *rest.next().unwrap() };
+                    }
+                    expected += buf.get_m(full).unwrap();
+                }
+                assert_eq!(*v, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_layout() {
+        let buf = XDBuf::<i32, 3>::new_with_vec([2, 3, 4], (0..24).collect()).unwrap();
+        let relayout = buf.relayout(Layout::LastMajor);
+
+        let a: XDBuf<i32, 2> = buf.max_axis(1).unwrap();
+        let b: XDBuf<i32, 2> = relayout.max_axis(1).unwrap();
+
+        assert_eq!(b.layout(), Layout::LastMajor);
+        for (index, v) in a.indexed_iter() {
+            assert_eq!(b.get_m(index), Some(v));
+        }
+    }
+
+    #[test]
+    fn skips_nan() {
+        let buf = XDBuf::<f64, 2>::new_with_vec([3, 1], vec![f64::NAN, 2.0, 1.0]).unwrap();
+
+        let min: XDBuf<f64, 1> = buf.min_axis(0).unwrap();
+        assert_eq!(min.as_slice(), &[1.0]);
+
+        let max: XDBuf<f64, 1> = buf.max_axis(0).unwrap();
+        assert_eq!(max.as_slice(), &[2.0]);
+
+        let nan = XDBuf::<f64, 2>::new([2, 2], f64::NAN).unwrap();
+        assert_eq!(nan.argmax(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_axis_and_dimension() {
+        let buf = XDBuf::<i32, 2>::new([2, 2], 0).unwrap();
+
+        assert!(buf.sum_axis::<1>(2).is_err());
+        assert!(buf.sum_axis::<2>(0).is_err());
+    }
+}
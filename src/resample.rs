@@ -0,0 +1,260 @@
+use std::f64::consts::PI;
+
+use num_traits::Float;
+
+use crate::storage::Storage;
+use crate::XDBuf;
+
+/// Filter used when resampling a buffer to a new size
+///
+/// バッファを新しいサイズに再標本化する際に使用するフィルタ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Filter {
+    /// The value of the nearest element.
+    ///
+    /// 最も近い要素の値。
+    Nearest,
+    /// Linear interpolation, widened when shrinking to average over the covered elements.
+    ///
+    /// 線形補間。縮小時は覆われる要素を平均するように広げられます。
+    #[default]
+    Linear,
+    /// The average of the elements weighted by the area they cover.
+    ///
+    /// 覆う面積で重み付けした要素の平均。
+    Box,
+    /// Lanczos filter with 3 lobes, widened when shrinking.
+    ///
+    /// 3つのローブを持つLanczosフィルタ。縮小時は広げられます。
+    Lanczos3,
+}
+
+impl<T: Float, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Generate a copy of the buffer resampled to `new_size` with `filter`.
+    ///
+    /// Each axis is resampled separately. Elements are treated as cells whose centers are aligned between the sizes,
+    /// and elements beyond the edge are treated as copies of the nearest edge element.
+    /// The result has the memory layout of this buffer.
+    ///
+    /// `filter`で`new_size`に再標本化したバッファの複製を生成します。
+    ///
+    /// 各軸は個別に再標本化されます。要素はサイズ間で中心が揃えられたセルとして扱われ、
+    /// 端の外側の要素は最も近い端の要素の複製として扱われます。
+    /// 結果はこのバッファのメモリ配置を持ちます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `new_size` exceeds the range of `usize`.
+    ///
+    /// * `new_size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Filter, XDBuf};
+    ///
+    /// let heightmap = XDBuf::<f32, 2>::new([512, 512], 0.0).unwrap()
+    ///     .map_indexed(|[x, y], _| (x + y) as f32);
+    ///
+    /// let small = heightmap.resample([128, 128], Filter::Box).unwrap();
+    /// assert_eq!(small.size(), [128, 128]);
+    ///
+    /// // 4x4の要素の平均
+    /// assert_eq!(small.get_m([0, 0]), Some(&3.0));
+    /// ```
+    pub fn resample(&self, new_size: [usize; D], filter: Filter) -> Result<XDBuf<T, D>, anyhow::Error> {
+        let mut dest = XDBuf::new_with_layout([1; D], T::zero(), self.layout)?;
+        self.resample_into(&mut dest, new_size, filter)?;
+
+        Ok(dest)
+    }
+
+    /// Writes this buffer resampled to `new_size` with `filter` into `dest`.
+    ///
+    /// `dest` is reinitialized with `init`, so its memory layout is kept and its allocated capacity is reused.
+    ///
+    /// `filter`で`new_size`に再標本化したこのバッファを`dest`に書き込みます。
+    ///
+    /// `dest`は`init`で再初期化されるため、メモリ配置が保たれ、割り当て済みの容量が再利用されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `new_size` exceeds the range of `usize`.
+    ///
+    /// * `new_size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Filter, XDBuf};
+    ///
+    /// let buf = XDBuf::<f64, 1>::new_with_vec([2], vec![0.0, 4.0]).unwrap();
+    /// let mut dest = XDBuf::<f64, 1>::new([16], 0.0).unwrap();
+    ///
+    /// buf.resample_into(&mut dest, [4], Filter::Linear).unwrap();
+    /// assert_eq!(dest.as_slice(), &[0.0, 1.0, 3.0, 4.0]);
+    /// ```
+    pub fn resample_into(&self, dest: &mut XDBuf<T, D>, new_size: [usize; D], filter: Filter) -> Result<(), anyhow::Error> {
+        XDBuf::<T, D>::calc_total_size(&new_size)?;
+
+        let mut axes = (0..D).filter(|&a| self.size[a] != new_size[a]).collect::<Vec<_>>();
+        if axes.is_empty() {
+            axes.push(0);
+        }
+
+        // 最後の軸以外は中間のバッファを経由する
+        let mut current: Option<XDBuf<T, D>> = None;
+        let mut size = self.size;
+
+        for (n, &axis) in axes.iter().enumerate() {
+            size[axis] = new_size[axis];
+            let weights = axis_weights(self.size[axis], new_size[axis], filter);
+
+            if n + 1 == axes.len() {
+                match &current {
+                    Some(src) => resample_axis(src, axis, &weights, size, dest)?,
+                    None => resample_axis(self, axis, &weights, size, dest)?,
+                }
+            } else {
+                let mut next = XDBuf::new_with_layout([1; D], T::zero(), self.layout)?;
+                match &current {
+                    Some(src) => resample_axis(src, axis, &weights, size, &mut next)?,
+                    None => resample_axis(self, axis, &weights, size, &mut next)?,
+                }
+                current = Some(next);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Resamples `src` along `axis` into `dest` of `size` using the per-element `weights`.
+///
+/// 要素ごとの`weights`を用いて、`src`を`axis`に沿って`size`の`dest`に再標本化します。
+fn resample_axis<T: Float, const D: usize, S: Storage<T>>(
+    src: &XDBuf<T, D, S>,
+    axis: usize,
+    weights: &[Vec<(usize, f64)>],
+    size: [usize; D],
+    dest: &mut XDBuf<T, D>,
+) -> Result<(), anyhow::Error> {
+    dest.init(size, T::zero())?;
+
+    for o in dest.idx_range() {
+        let index = dest.to_mul_dim_index(o);
+        let base = index.iter().zip(src.stride).enumerate()
+            .filter(|&(a, _)| a != axis)
+            .map(|(_, (&i, s))| i * s)
+            .sum::<usize>();
+
+        dest.buf[o] = weights[index[axis]].iter().fold(T::zero(), |acc, &(i, w)| {
+            // 浮動小数点数型への変換は失敗しない
+            acc + src.as_slice()[base + i * src.stride[axis]] * T::from(w).unwrap()
+        });
+    }
+
+    Ok(())
+}
+
+/// Computes the normalized source indices and weights for each element of an axis resampled from `src_len` to `dst_len`.
+///
+/// `src_len`から`dst_len`に再標本化する軸の各要素について、正規化された元のインデックスと重みを計算します。
+fn axis_weights(src_len: usize, dst_len: usize, filter: Filter) -> Vec<Vec<(usize, f64)>> {
+    let scale = src_len as f64 / dst_len as f64;
+    let last = src_len as isize - 1;
+
+    (0..dst_len).map(|d| {
+        // 中心を揃えた元の座標
+        let center = (d as f64 + 0.5) * scale - 0.5;
+
+        let mut weights = match filter {
+            Filter::Nearest => {
+                let i = (center.round() as isize).clamp(0, last);
+                return vec![(i as usize, 1.0)];
+            }
+            Filter::Box => {
+                let (start, end) = (d as f64 * scale, (d + 1) as f64 * scale);
+
+                (start.floor() as isize..end.ceil() as isize).map(|i| {
+                    let overlap = end.min(i as f64 + 1.0) - start.max(i as f64);
+                    (i, overlap.max(0.0))
+                }).collect::<Vec<_>>()
+            }
+            Filter::Linear | Filter::Lanczos3 => {
+                let (support, kernel): (f64, fn(f64) -> f64) = match filter {
+                    Filter::Linear => (1.0, |x| (1.0 - x.abs()).max(0.0)),
+                    _ => (3.0, lanczos3),
+                };
+
+                // 縮小時はカーネルを広げて覆われる要素をすべて考慮する
+                let widen = scale.max(1.0);
+                let radius = support * widen;
+
+                ((center - radius).floor() as isize..=(center + radius).ceil() as isize)
+                    .map(|i| (i, kernel((i as f64 - center) / widen)))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        weights.retain(|&(_, w)| w != 0.0);
+
+        let total = weights.iter().map(|(_, w)| w).sum::<f64>();
+        weights.iter().map(|&(i, w)| (i.clamp(0, last) as usize, w / total)).collect()
+    }).collect()
+}
+
+fn lanczos3(x: f64) -> f64 {
+    if x == 0.0 {
+        return 1.0;
+    }
+
+    if x.abs() >= 3.0 {
+        return 0.0;
+    }
+
+    let px = PI * x;
+    3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn every_filter_preserves_constant_buffer() {
+        let buf = XDBuf::<f64, 3>::new([7, 4, 5], 2.5).unwrap();
+
+        for filter in [Filter::Nearest, Filter::Linear, Filter::Box, Filter::Lanczos3] {
+            let resampled = buf.resample([3, 9, 5], filter).unwrap();
+
+            assert_eq!(resampled.size(), [3, 9, 5]);
+            assert!(resampled.iter().all(|v| (v - 2.5).abs() < 1e-12), "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn same_size_is_identity_and_keeps_destination_layout() {
+        let buf = XDBuf::<f32, 2>::new_with_vec([3, 2], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let mut dest = XDBuf::<f32, 2>::new_with_layout([10, 10], 0.0, Layout::LastMajor).unwrap();
+
+        for filter in [Filter::Nearest, Filter::Linear, Filter::Box, Filter::Lanczos3] {
+            buf.resample_into(&mut dest, [3, 2], filter).unwrap();
+
+            assert_eq!(dest.layout(), Layout::LastMajor);
+            for (index, v) in buf.indexed_iter() {
+                assert!((dest.get_m(index).unwrap() - v).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_upsampling_repeats_elements() {
+        let buf = XDBuf::<f32, 1>::new_with_vec([3], vec![1.0, 2.0, 3.0]).unwrap();
+        let resampled = buf.resample([6], Filter::Nearest).unwrap();
+
+        assert_eq!(resampled.as_slice(), &[1.0, 1.0, 2.0, 2.0, 3.0, 3.0]);
+    }
+}
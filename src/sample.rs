@@ -0,0 +1,273 @@
+use num_traits::Float;
+
+use crate::storage::Storage;
+use crate::XDBuf;
+
+/// Interpolation used when sampling at fractional coordinates
+///
+/// 小数の座標で標本化する際に使用する補間
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Interp {
+    /// The value of the nearest element.
+    ///
+    /// 最も近い要素の値。
+    Nearest,
+    /// Linear interpolation along every axis (bilinear in 2D, trilinear in 3D).
+    ///
+    /// すべての軸に沿った線形補間(2次元では双線形、3次元では三線形)。
+    #[default]
+    Linear,
+    /// Catmull-Rom cubic interpolation along every axis.
+    ///
+    /// すべての軸に沿ったCatmull-Romの3次補間。
+    Cubic,
+}
+
+/// Value used for elements referenced outside the buffer while sampling
+///
+/// 標本化の際にバッファの外側で参照された要素に使用する値
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Boundary<T> {
+    /// The value of the nearest element on the edge.
+    ///
+    /// 端にある最も近い要素の値。
+    Clamp,
+    /// The value of the element on the opposite side, as if the buffer repeated periodically.
+    ///
+    /// バッファが周期的に繰り返されるものとした、反対側の要素の値。
+    Wrap,
+    /// The given value.
+    ///
+    /// 指定された値。
+    Constant(T),
+}
+
+impl<T: Float, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Samples the buffer at the fractional position `pos`.
+    ///
+    /// The element at index `i` is located at position `i`.
+    ///
+    /// 小数の位置`pos`でバッファを標本化します。
+    ///
+    /// インデックス`i`の要素は位置`i`にあります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Boundary, Interp, XDBuf};
+    ///
+    /// // [0, 10,
+    /// //  20, 30]
+    /// let buf = XDBuf::<f64, 2>::new_with_vec([2, 2], vec![0.0, 10.0, 20.0, 30.0]).unwrap();
+    ///
+    /// assert_eq!(buf.sample([0.5, 0.5], Interp::Linear, Boundary::Clamp), 15.0);
+    /// assert_eq!(buf.sample([0.8, 0.1], Interp::Nearest, Boundary::Clamp), 10.0);
+    /// assert_eq!(buf.sample([-1.0, 0.0], Interp::Linear, Boundary::Constant(-1.0)), -1.0);
+    /// ```
+    pub fn sample(&self, pos: [f64; D], interp: Interp, boundary: Boundary<T>) -> T {
+        let taps = pos.map(|p| Taps::new(p, interp, false));
+
+        self.convolve(&taps, boundary)
+    }
+
+    /// Samples the partial derivative along each axis at the fractional position `pos`.
+    ///
+    /// The derivative of the interpolating function is used for `Interp::Linear` and `Interp::Cubic`,
+    /// and the central difference of the nearest elements for `Interp::Nearest`.
+    ///
+    /// 小数の位置`pos`で各軸に沿った偏微分を標本化します。
+    ///
+    /// `Interp::Linear`と`Interp::Cubic`では補間関数の導関数を、
+    /// `Interp::Nearest`では最も近い要素の中心差分を使用します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Boundary, Interp, XDBuf};
+    ///
+    /// // f(x, y) = 2x + 3y
+    /// let buf = XDBuf::<f32, 2>::new([4, 4], 0.0).unwrap()
+    ///     .map_indexed(|[x, y], _| (2 * x + 3 * y) as f32);
+    ///
+    /// let gradient = buf.gradient([1.5, 1.25], Interp::Cubic, Boundary::Clamp);
+    /// assert!((gradient[0] - 2.0).abs() < 1e-5);
+    /// assert!((gradient[1] - 3.0).abs() < 1e-5);
+    /// ```
+    pub fn gradient(&self, pos: [f64; D], interp: Interp, boundary: Boundary<T>) -> [T; D] {
+        let values = pos.map(|p| Taps::new(p, interp, false));
+        let derivatives = pos.map(|p| Taps::new(p, interp, true));
+
+        std::array::from_fn(|axis| {
+            let mut taps = values;
+            taps[axis] = derivatives[axis];
+
+            self.convolve(&taps, boundary)
+        })
+    }
+
+    /// Computes the weighted sum over the tensor product of `taps`.
+    ///
+    /// `taps`のテンソル積についての重み付き和を計算します。
+    fn convolve(&self, taps: &[Taps; D], boundary: Boundary<T>) -> T {
+        let mut acc = T::zero();
+        let mut n = [0; D];
+
+        loop {
+            let mut weight = 1.0;
+            let mut index = [0; D];
+            let mut outside = false;
+
+            for axis in 0..D {
+                let (i, w) = taps[axis].taps[n[axis]];
+                weight *= w;
+
+                match resolve(i, self.size[axis], &boundary) {
+                    Some(i) => index[axis] = i,
+                    None => outside = true,
+                }
+            }
+
+            if weight != 0.0 {
+                let value = match boundary {
+                    Boundary::Constant(c) if outside => c,
+                    // 境界の処理で解決されたインデックスは必ず範囲内
+                    _ => *self.get_m(index).unwrap(),
+                };
+
+                // 浮動小数点数型への変換は失敗しない
+                acc = acc + value * T::from(weight).unwrap();
+            }
+
+            let carried = n.iter_mut().zip(taps).all(|(n, taps)| {
+                *n += 1;
+                if *n < taps.len {
+                    false
+                } else {
+                    *n = 0;
+                    true
+                }
+            });
+
+            if carried {
+                return acc;
+            }
+        }
+    }
+}
+
+/// Resolves the possibly out-of-range `index` along an axis of `size` elements.
+///
+/// Returns `None` if the index refers to `Boundary::Constant`.
+///
+/// 範囲外の可能性がある`index`を、`size`個の要素を持つ軸について解決します。
+///
+/// `Boundary::Constant`を参照する場合は`None`を返します。
+fn resolve<T>(index: i64, size: usize, boundary: &Boundary<T>) -> Option<usize> {
+    let last = i64::try_from(size - 1).unwrap_or(i64::MAX);
+
+    match boundary {
+        Boundary::Clamp => Some(index.clamp(0, last) as usize),
+        Boundary::Wrap => Some(index.rem_euclid(last + 1) as usize),
+        Boundary::Constant(_) => (0..=last).contains(&index).then_some(index as usize),
+    }
+}
+
+/// Indices and weights contributing to a sample along a single axis
+///
+/// 1つの軸に沿った標本に寄与するインデックスと重み
+#[derive(Debug, Clone, Copy)]
+struct Taps {
+    taps: [(i64, f64); 4],
+    len: usize,
+}
+
+impl Taps {
+    fn new(pos: f64, interp: Interp, derivative: bool) -> Self {
+        let base = pos.floor();
+        let t = pos - base;
+        let i = base as i64;
+
+        match (interp, derivative) {
+            (Interp::Nearest, false) => Self::from_slice(&[(pos.round() as i64, 1.0)]),
+            (Interp::Nearest, true) => {
+                let i = pos.round() as i64;
+                Self::from_slice(&[(i.saturating_sub(1), -0.5), (i.saturating_add(1), 0.5)])
+            }
+            (Interp::Linear, false) => Self::from_slice(&[(i, 1.0 - t), (i.saturating_add(1), t)]),
+            (Interp::Linear, true) => Self::from_slice(&[(i, -1.0), (i.saturating_add(1), 1.0)]),
+            (Interp::Cubic, false) => {
+                let (t2, t3) = (t * t, t * t * t);
+                Self::cubic(i, [
+                    (-t3 + 2.0 * t2 - t) / 2.0,
+                    (3.0 * t3 - 5.0 * t2 + 2.0) / 2.0,
+                    (-3.0 * t3 + 4.0 * t2 + t) / 2.0,
+                    (t3 - t2) / 2.0,
+                ])
+            }
+            (Interp::Cubic, true) => {
+                let t2 = t * t;
+                Self::cubic(i, [
+                    (-3.0 * t2 + 4.0 * t - 1.0) / 2.0,
+                    (9.0 * t2 - 10.0 * t) / 2.0,
+                    (-9.0 * t2 + 8.0 * t + 1.0) / 2.0,
+                    (3.0 * t2 - 2.0 * t) / 2.0,
+                ])
+            }
+        }
+    }
+
+    fn cubic(i: i64, weights: [f64; 4]) -> Self {
+        Self {
+            taps: std::array::from_fn(|k| (i.saturating_add(k as i64 - 1), weights[k])),
+            len: 4,
+        }
+    }
+
+    fn from_slice(taps: &[(i64, f64)]) -> Self {
+        let mut this = Self {
+            taps: [(0, 0.0); 4],
+            len: taps.len(),
+        };
+        this.taps[..taps.len()].copy_from_slice(taps);
+
+        this
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn linear_reproduces_trilinear_function() {
+        let f = |[x, y, z]: [f64; 3]| 1.0 + 2.0 * x - y + 0.5 * z + x * y * z;
+        let buf = XDBuf::<f64, 3>::new([3, 3, 3], 0.0).unwrap()
+            .map_indexed(|[x, y, z], _| f([x as f64, y as f64, z as f64]));
+
+        for pos in [[0.25, 1.5, 0.75], [1.9, 0.1, 1.0], [2.0, 2.0, 2.0]] {
+            assert!((buf.sample(pos, Interp::Linear, Boundary::Clamp) - f(pos)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn cubic_passes_through_elements_and_wraps() {
+        let buf = XDBuf::<f32, 1>::new_with_vec([4], vec![1.0, 5.0, 2.0, 8.0]).unwrap();
+
+        for (i, v) in buf.indexed_iter() {
+            assert_eq!(buf.sample([i[0] as f64], Interp::Cubic, Boundary::Wrap), *v);
+        }
+        assert_eq!(buf.sample([4.0], Interp::Cubic, Boundary::Wrap), 1.0);
+        assert_eq!(buf.sample([-1.0], Interp::Nearest, Boundary::Wrap), 8.0);
+        assert_eq!(buf.sample([-3.0], Interp::Nearest, Boundary::Clamp), 1.0);
+    }
+
+    #[test]
+    fn gradient_uses_boundary() {
+        let buf = XDBuf::<f64, 1>::new_with_vec([3], vec![0.0, 1.0, 2.0]).unwrap();
+
+        assert_eq!(buf.gradient([1.0], Interp::Nearest, Boundary::Clamp), [1.0]);
+        assert_eq!(buf.gradient([2.0], Interp::Nearest, Boundary::Clamp), [0.5]);
+        assert_eq!(buf.gradient([2.0], Interp::Nearest, Boundary::Constant(4.0)), [1.5]);
+        assert_eq!(buf.gradient([0.5], Interp::Linear, Boundary::Clamp), [1.0]);
+    }
+}
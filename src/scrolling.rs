@@ -0,0 +1,395 @@
+use std::ops::Range;
+
+use anyhow::anyhow;
+
+use crate::XDBuf;
+
+/// Structure representing a fixed-size window onto a larger n-dimensional space that can be scrolled without copying
+///
+/// Elements are stored in a ring buffer in each dimension, so scrolling only rewrites the newly exposed slabs.
+/// Logical indices are relative to the window, and the window is located at the world coordinate `origin`.
+///
+/// より大きなn次元空間に対する固定サイズの窓を表し、複製せずにスクロールできる構造体です。
+///
+/// 要素は各次元についてリングバッファに格納されるため、スクロールでは新たに現れる領域のみが書き換えられます。
+/// 論理的なインデックスは窓に対する相対位置で、窓はワールド座標`origin`に位置します。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::ScrollingXDBuf;
+///
+/// // カメラの周囲5x5の範囲
+/// let mut window = ScrollingXDBuf::<i32, 2>::new([5, 5], [-2, -2], 0).unwrap();
+/// window.set_m([4, 2], 1).unwrap(); // ワールド座標(2, 0)
+///
+/// // カメラを右に1つ移動し、新たに現れた列をワールド座標から生成
+/// window.scroll([1, 0], |[x, y]| (x * 10 + y) as i32).unwrap();
+///
+/// assert_eq!(window.origin(), [-1, -2]);
+/// assert_eq!(window.get_m([3, 2]), Some(&1));
+/// assert_eq!(window.get_m([4, 0]), Some(&28));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScrollingXDBuf<T, const D: usize> {
+    buf: XDBuf<T, D>,
+    origin: [isize; D],
+    offset: [usize; D],
+}
+
+impl<T, const D: usize> ScrollingXDBuf<T, D> {
+    /// Generate a new `ScrollingXDBuf` of `size` located at the world coordinate `origin`.
+    ///
+    /// ワールド座標`origin`に位置する`size`の新しい`ScrollingXDBuf`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    /// * Error if the covered world coordinates exceed the range of `isize`.
+    ///
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    /// * 覆われるワールド座標が`isize`の範囲を超える場合エラーになります。
+    pub fn new(size: [usize; D], origin: [isize; D], initial_value: T) -> Result<Self, anyhow::Error>
+    where
+        T: Clone,
+    {
+        let buf = XDBuf::new(size, initial_value)?;
+        Self::validate_origin(&size, &origin)?;
+
+        Ok(Self {
+            buf,
+            origin,
+            offset: [0; D],
+        })
+    }
+
+    /// Returns the world coordinate of the element at logical index `[0; D]`.
+    ///
+    /// 論理的なインデックス`[0; D]`の要素のワールド座標を返します。
+    pub fn origin(&self) -> [isize; D] {
+        self.origin
+    }
+
+    /// Returns the size of each dimension of the window.
+    ///
+    /// 窓の各次元のサイズを返します。
+    pub fn size(&self) -> [usize; D] {
+        self.buf.size()
+    }
+
+    /// Returns the number of elements in the window.
+    ///
+    /// 窓の要素数を返します。
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if the window contains no elements.
+    ///
+    /// 窓が要素を持たない場合に`true`を返します。
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Convert a logical index in array notation to the scalar index of the underlying storage.
+    ///
+    /// 論理的な配列表記のインデックスを、内部のストレージのスカラーのインデックスに変換します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    pub fn to_storage_index(&self, index: &[usize; D]) -> Result<usize, anyhow::Error> {
+        self.buf.validate_index(index)?;
+
+        Ok(self.storage_index(index))
+    }
+
+    /// Convert a logical index in array notation to a world coordinate.
+    ///
+    /// 論理的な配列表記のインデックスをワールド座標に変換します。
+    pub fn to_world(&self, index: [usize; D]) -> [isize; D] {
+        let mut world = self.origin;
+
+        // 窓の内側の座標は`isize`に収まることを検証済み
+        world.iter_mut().zip(index).for_each(|(w, i)| *w = w.wrapping_add_unsigned(i));
+
+        world
+    }
+
+    /// Get a reference to the element specified by the logical `index` in array notation.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// 論理的な配列表記の`index`で指定された要素の参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get_m(&self, index: [usize; D]) -> Option<&T> {
+        let scalar = self.to_storage_index(&index).ok()?;
+
+        self.buf.get(scalar)
+    }
+
+    /// Get a variable reference to the element specified by the logical `index` in array notation.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// 論理的な配列表記の`index`で指定された要素の可変参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get_mut_m(&mut self, index: [usize; D]) -> Option<&mut T> {
+        let scalar = self.to_storage_index(&index).ok()?;
+
+        self.buf.get_mut(scalar)
+    }
+
+    /// Set `value` to the element specified by the logical `index` in array notation.
+    ///
+    /// 論理的な配列表記の`index`で指定された要素に`value`を設定します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    pub fn set_m(&mut self, index: [usize; D], value: T) -> Result<(), anyhow::Error> {
+        *self.get_mut_m(index).ok_or(anyhow!("index is out of range"))? = value;
+
+        Ok(())
+    }
+
+    /// Get a reference to the element at the world coordinate `world`.
+    ///
+    /// Returns `None` if `world` is outside the window.
+    ///
+    /// ワールド座標`world`の要素の参照を取得します。
+    ///
+    /// `world`が窓の外側にある場合は`None`を返します。
+    pub fn get_world(&self, world: [isize; D]) -> Option<&T> {
+        let mut index = [0; D];
+
+        for ((i, w), o) in index.iter_mut().zip(world).zip(self.origin) {
+            if w < o {
+                return None;
+            }
+            *i = w.abs_diff(o);
+        }
+
+        self.get_m(index)
+    }
+
+    /// Returns an iterator over the logical indices and elements in the logical order.
+    ///
+    /// 論理的なインデックスと要素を論理的な順序で走査するイテレータを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::ScrollingXDBuf;
+    ///
+    /// let mut window = ScrollingXDBuf::<isize, 1>::new([4], [0], 0).unwrap();
+    /// window.scroll([2], |[x]| x).unwrap();
+    ///
+    /// let values = window.indexed_iter().map(|(_, &v)| v).collect::<Vec<_>>();
+    /// assert_eq!(values, vec![0, 0, 4, 5]);
+    /// ```
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ([usize; D], &T)> + '_ {
+        self.buf.idx_range().map(move |i| {
+            let index = self.buf.to_mul_dim_index(i);
+            (index, &self.buf.as_slice()[self.storage_index(&index)])
+        })
+    }
+
+    /// Returns an iterator over the elements in the logical order.
+    ///
+    /// 要素を論理的な順序で走査するイテレータを返します。
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.indexed_iter().map(|(_, v)| v)
+    }
+
+    /// Generate an `XDBuf` with the elements of the window in the logical order.
+    ///
+    /// 窓の要素を論理的な順序で並べた`XDBuf`を生成します。
+    pub fn to_xdbuf(&self) -> XDBuf<T, D>
+    where
+        T: Clone,
+    {
+        // インデックスは窓の内側なので必ず要素が存在する
+        self.buf.map_indexed(|index, _| self.get_m(index).unwrap().clone())
+    }
+
+    /// Moves the window by `delta` without copying the elements that remain inside.
+    ///
+    /// Each newly exposed element is set to the result of `fill` called with its world coordinate.
+    /// The cost is proportional to the number of newly exposed elements.
+    ///
+    /// 内側に残る要素を複製せずに、窓を`delta`だけ移動します。
+    ///
+    /// 新たに現れる各要素には、そのワールド座標で`fill`を呼び出した結果が設定されます。
+    /// コストは新たに現れる要素の数に比例します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the covered world coordinates after the move exceed the range of `isize`.
+    ///
+    /// * 移動後に覆われるワールド座標が`isize`の範囲を超える場合エラーになります。
+    pub fn scroll(&mut self, delta: [isize; D], fill: impl FnMut([isize; D]) -> T) -> Result<(), anyhow::Error> {
+        let mut origin = self.origin;
+        for ((o, d), s) in origin.iter_mut().zip(delta).zip(self.buf.size()) {
+            *o = o.checked_add(d).ok_or(anyhow!("origin is out of range"))?;
+            Self::validate_origin(&[s], &[*o])?;
+        }
+
+        self.scroll_unchecked(origin, delta, fill);
+
+        Ok(())
+    }
+
+    /// Moves the window so that `origin` becomes its origin.
+    ///
+    /// `origin`が原点になるように窓を移動します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the covered world coordinates after the move exceed the range of `isize`.
+    ///
+    /// * 移動後に覆われるワールド座標が`isize`の範囲を超える場合エラーになります。
+    pub fn scroll_to(&mut self, origin: [isize; D], fill: impl FnMut([isize; D]) -> T) -> Result<(), anyhow::Error> {
+        Self::validate_origin(&self.buf.size(), &origin)?;
+
+        let mut delta = [0; D];
+        for ((d, new), old) in delta.iter_mut().zip(origin).zip(self.origin) {
+            // 差が`isize`に収まらない場合は窓全体が入れ替わる
+            *d = new.checked_sub(old).unwrap_or(if new > old { isize::MAX } else { isize::MIN });
+        }
+
+        self.scroll_unchecked(origin, delta, fill);
+
+        Ok(())
+    }
+
+    fn scroll_unchecked(&mut self, origin: [isize; D], delta: [isize; D], mut fill: impl FnMut([isize; D]) -> T) {
+        let size = self.buf.size();
+
+        let mut exposed: [Range<usize>; D] = std::array::from_fn(|_| 0..0);
+        for (a, ((e, d), s)) in exposed.iter_mut().zip(delta).zip(size).enumerate() {
+            let shift = d.unsigned_abs().min(s);
+
+            self.offset[a] = if d >= 0 {
+                (self.offset[a] + shift) % s
+            } else {
+                (self.offset[a] + s - shift) % s
+            };
+
+            *e = if d >= 0 { s - shift..s } else { 0..shift };
+        }
+
+        self.origin = origin;
+
+        // 新たに現れる領域を、軸ごとに重複しないように走査する
+        for a in 0..D {
+            let mut ranges: [Range<usize>; D] = std::array::from_fn(|b| 0..size[b]);
+            ranges[a] = exposed[a].clone();
+
+            for (b, r) in ranges.iter_mut().enumerate().take(a) {
+                if exposed[b].start == 0 {
+                    r.start = exposed[b].end;
+                } else {
+                    r.end = exposed[b].start;
+                }
+            }
+
+            if ranges.iter().any(|r| r.is_empty()) {
+                continue;
+            }
+
+            let mut index = ranges.clone().map(|r| r.start);
+            loop {
+                let scalar = self.storage_index(&index);
+                self.buf.as_mut_slice()[scalar] = fill(self.to_world(index));
+
+                let carried = index.iter_mut().zip(&ranges).all(|(i, r)| {
+                    *i += 1;
+                    if *i < r.end {
+                        false
+                    } else {
+                        *i = r.start;
+                        true
+                    }
+                });
+
+                if carried {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn storage_index(&self, index: &[usize; D]) -> usize {
+        let size = self.buf.size();
+        let mut physical = [0; D];
+
+        for (a, p) in physical.iter_mut().enumerate() {
+            *p = (index[a] + self.offset[a]) % size[a];
+        }
+
+        physical.iter().zip(self.buf.stride()).map(|(&p, &s)| p * s).sum()
+    }
+
+    fn validate_origin<const N: usize>(size: &[usize; N], origin: &[isize; N]) -> Result<(), anyhow::Error> {
+        let fits = size.iter().zip(origin).all(|(&s, &o)| {
+            s.checked_sub(1).is_some_and(|s| o.checked_add_unsigned(s).is_some())
+        });
+
+        if fits {
+            Ok(())
+        } else {
+            Err(anyhow!("origin is out of range"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn world_value([x, y, z]: [isize; 3]) -> isize {
+        x * 10000 + y * 100 + z
+    }
+
+    #[test]
+    fn scrolling_matches_regenerated_window() {
+        let size = [4, 3, 5];
+        let mut window = ScrollingXDBuf::<isize, 3>::new(size, [0, 0, 0], 0).unwrap();
+        window.scroll_to([0, 0, 0], world_value).unwrap();
+        window.scroll([0, 0, 5], world_value).unwrap();
+        window.scroll([0, 0, -5], world_value).unwrap();
+
+        let mut fills = 0;
+        for delta in [[1, 0, 0], [-2, 1, 3], [0, -1, -7], [10, 0, 0], [-1, -1, -1]] {
+            window.scroll(delta, |w| {
+                fills += 1;
+                world_value(w)
+            }).unwrap();
+
+            for (index, &v) in window.indexed_iter() {
+                assert_eq!(v, world_value(window.to_world(index)));
+            }
+        }
+
+        // 窓の要素数60に対し、各移動で現れた要素のみが生成される
+        assert_eq!(fills, 15 + 52 + 60 + 60 + 36);
+        assert_eq!(window.to_xdbuf().get_m([0, 0, 0]), Some(&world_value(window.origin())));
+    }
+
+    #[test]
+    fn rejects_origin_beyond_isize() {
+        let mut window = ScrollingXDBuf::<u8, 1>::new([4], [isize::MAX - 4], 0).unwrap();
+
+        assert!(window.scroll([1], |_| 0).is_ok());
+        assert!(window.scroll([1], |_| 0).is_err());
+        assert_eq!(window.origin(), [isize::MAX - 3]);
+        assert_eq!(window.get_world([isize::MAX]), Some(&0));
+    }
+}
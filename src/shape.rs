@@ -0,0 +1,543 @@
+use crate::storage::{Storage, StorageMut};
+use crate::XDBuf;
+
+/// Rule deciding which parts of a self-intersecting polygon are inside
+///
+/// 自己交差する多角形のどの部分が内側かを決める規則
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FillRule {
+    /// Inside if a ray from the point crosses the outline an odd number of times.
+    ///
+    /// 点からの光線が輪郭と奇数回交差する場合に内側。
+    #[default]
+    EvenOdd,
+    /// Inside if the outline winds around the point a nonzero number of times.
+    ///
+    /// 輪郭が点の周りを0以外の回数だけ回る場合に内側。
+    NonZero,
+}
+
+/// Iterator over the indices in array notation inside a box, clipped to the size of a buffer
+///
+/// Indices are visited with the first axis changing fastest.
+///
+/// バッファのサイズに切り詰められた直方体の内側の、配列表記のインデックスを走査するイテレータです。
+///
+/// インデックスは最初の軸が最も速く変化する順に走査されます。
+#[derive(Debug, Clone)]
+pub struct BoxRegion<const D: usize> {
+    min: [usize; D],
+    max: [usize; D],
+    current: Option<[usize; D]>,
+}
+
+impl<const D: usize> BoxRegion<D> {
+    /// Generate a region from `min` to `max`, both inclusive, clipped to `size`.
+    ///
+    /// `size`に切り詰められた、両端を含む`min`から`max`までの領域を生成します。
+    pub fn new(size: [usize; D], min: [isize; D], max: [isize; D]) -> Self {
+        let lo = std::array::from_fn(|a| min[a].max(0) as usize);
+        let hi = std::array::from_fn(|a| max[a].min(size[a] as isize - 1));
+
+        let empty = (0..D).any(|a| hi[a] < lo[a] as isize);
+
+        Self {
+            min: lo,
+            max: hi.map(|h| h.max(0) as usize),
+            current: (!empty).then_some(lo),
+        }
+    }
+}
+
+impl<const D: usize> Iterator for BoxRegion<D> {
+    type Item = [usize; D];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.current?;
+
+        let mut next = index;
+        let carried = (0..D).all(|a| {
+            if next[a] < self.max[a] {
+                next[a] += 1;
+                false
+            } else {
+                next[a] = self.min[a];
+                true
+            }
+        });
+        self.current = (!carried).then_some(next);
+
+        Some(index)
+    }
+}
+
+/// Iterator over the indices in array notation inside an axis-aligned ellipsoid, clipped to the size of a buffer
+///
+/// An element is inside if its center, located at `i + 0.5` along each axis, is inside or on the ellipsoid.
+///
+/// バッファのサイズに切り詰められた、軸に沿った楕円体の内側の配列表記のインデックスを走査するイテレータです。
+///
+/// 各軸に沿って`i + 0.5`にある要素の中心が楕円体の内側または表面上にある場合に、その要素は内側になります。
+#[derive(Debug, Clone)]
+pub struct EllipsoidRegion<const D: usize> {
+    bounds: BoxRegion<D>,
+    center: [f64; D],
+    radii: [f64; D],
+}
+
+impl<const D: usize> EllipsoidRegion<D> {
+    /// Generate a region of the ellipsoid with `center` and `radii`, clipped to `size`.
+    ///
+    /// `size`に切り詰められた、中心`center`と半径`radii`の楕円体の領域を生成します。
+    pub fn new(size: [usize; D], center: [f64; D], radii: [f64; D]) -> Self {
+        let min = std::array::from_fn(|a| (center[a] - radii[a] - 0.5).ceil() as isize);
+        let max = std::array::from_fn(|a| (center[a] + radii[a] - 0.5).floor() as isize);
+
+        Self {
+            bounds: BoxRegion::new(size, min, max),
+            center,
+            radii,
+        }
+    }
+
+    fn contains(&self, index: &[usize; D]) -> bool {
+        let distance = (0..D).map(|a| {
+            let d = index[a] as f64 + 0.5 - self.center[a];
+            if self.radii[a] == 0.0 {
+                if d == 0.0 { 0.0 } else { f64::INFINITY }
+            } else {
+                (d / self.radii[a]).powi(2)
+            }
+        }).sum::<f64>();
+
+        distance <= 1.0
+    }
+}
+
+impl<const D: usize> Iterator for EllipsoidRegion<D> {
+    type Item = [usize; D];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let index = self.bounds.next()?;
+            if self.contains(&index) {
+                return Some(index);
+            }
+        }
+    }
+}
+
+/// Iterator over the indices in array notation inside a polygon, clipped to the size of a 2-dimensional buffer
+///
+/// The polygon is rasterized row by row. An element is inside if its center, located at `i + 0.5` along each axis,
+/// is inside the polygon, with the right and bottom edges excluded so that adjacent polygons do not overlap.
+///
+/// 2次元のバッファのサイズに切り詰められた、多角形の内側の配列表記のインデックスを走査するイテレータです。
+///
+/// 多角形は行ごとにラスタライズされます。各軸に沿って`i + 0.5`にある要素の中心が多角形の内側にある場合に、その要素は内側になります。
+/// 隣接する多角形が重ならないように、右と下の辺は含まれません。
+#[derive(Debug, Clone)]
+pub struct PolygonRegion {
+    edges: Vec<[[f64; 2]; 2]>,
+    rule: FillRule,
+    width: usize,
+    row: usize,
+    next_row: usize,
+    end_row: usize,
+    spans: Vec<(usize, usize)>,
+    span: usize,
+    x: usize,
+}
+
+impl PolygonRegion {
+    /// Generate a region of the polygon with the vertices `points`, clipped to `size`.
+    ///
+    /// The last vertex is connected to the first one.
+    ///
+    /// `size`に切り詰められた、頂点`points`を持つ多角形の領域を生成します。
+    ///
+    /// 最後の頂点は最初の頂点に接続されます。
+    pub fn new(size: [usize; 2], points: &[[f64; 2]], rule: FillRule) -> Self {
+        let edges = (0..points.len())
+            .map(|k| [points[k], points[(k + 1) % points.len()]])
+            .filter(|[a, b]| a[1] != b[1])
+            .collect::<Vec<_>>();
+
+        let (min_y, max_y) = edges.iter().flatten().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(p[1]), hi.max(p[1]))
+        });
+
+        let clip = |y: f64| ((y - 0.5).ceil().max(0.0) as usize).min(size[1]);
+        let (next_row, end_row) = if edges.is_empty() { (0, 0) } else { (clip(min_y), clip(max_y)) };
+
+        Self {
+            edges,
+            rule,
+            width: size[0],
+            row: 0,
+            next_row,
+            end_row,
+            spans: Vec::new(),
+            span: 0,
+            x: 0,
+        }
+    }
+
+    /// Computes the spans of the inside elements on `row`.
+    ///
+    /// `row`上の内側の要素の区間を計算します。
+    fn scan(&mut self, row: usize) {
+        let y = row as f64 + 0.5;
+
+        // 上端を含み下端を含まない辺との交差
+        let mut crossings = self.edges.iter().filter_map(|&[a, b]| {
+            let (lo, hi) = if a[1] < b[1] { (a, b) } else { (b, a) };
+            (lo[1] <= y && y < hi[1]).then(|| {
+                let x = a[0] + (y - a[1]) * (b[0] - a[0]) / (b[1] - a[1]);
+                (x, if a[1] < b[1] { 1 } else { -1 })
+            })
+        }).collect::<Vec<_>>();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let clip = |x: f64| ((x - 0.5).ceil().max(0.0) as usize).min(self.width);
+
+        self.spans.clear();
+        let mut winding = 0;
+        for (k, pair) in crossings.windows(2).enumerate() {
+            winding += pair[0].1;
+
+            let inside = match self.rule {
+                FillRule::EvenOdd => k % 2 == 0,
+                FillRule::NonZero => winding != 0,
+            };
+
+            let (start, end) = (clip(pair[0].0), clip(pair[1].0));
+            if inside && start < end {
+                self.spans.push((start, end));
+            }
+        }
+
+        self.row = row;
+        self.span = 0;
+        self.x = self.spans.first().map_or(0, |s| s.0);
+    }
+}
+
+impl Iterator for PolygonRegion {
+    type Item = [usize; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&(_, end)) = self.spans.get(self.span) {
+                if self.x < end {
+                    self.x += 1;
+                    return Some([self.x - 1, self.row]);
+                }
+
+                self.span += 1;
+                if let Some(&(start, _)) = self.spans.get(self.span) {
+                    self.x = start;
+                }
+                continue;
+            }
+
+            if self.next_row >= self.end_row {
+                return None;
+            }
+
+            let row = self.next_row;
+            self.next_row += 1;
+            self.scan(row);
+        }
+    }
+}
+
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Returns the region of the box from `min` to `max`, both inclusive, clipped to the size of the buffer.
+    ///
+    /// バッファのサイズに切り詰められた、両端を含む`min`から`max`までの直方体の領域を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 2>::new([4, 4], 0).unwrap();
+    ///
+    /// let region = buf.box_region([-2, 2], [1, 5]).collect::<Vec<_>>();
+    /// assert_eq!(region, vec![[0, 2], [1, 2], [0, 3], [1, 3]]);
+    /// ```
+    pub fn box_region(&self, min: [isize; D], max: [isize; D]) -> BoxRegion<D> {
+        BoxRegion::new(self.size, min, max)
+    }
+
+    /// Returns the region of the ball with `center` and `radius`, clipped to the size of the buffer.
+    ///
+    /// The element at index `i` is centered at `i + 0.5` along each axis.
+    ///
+    /// バッファのサイズに切り詰められた、中心`center`と半径`radius`の球の領域を返します。
+    ///
+    /// インデックス`i`の要素の中心は各軸に沿って`i + 0.5`にあります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 3>::new([8, 8, 8], 0).unwrap();
+    ///
+    /// // 中心の要素と面で接する6つの要素
+    /// assert_eq!(buf.ball_region([3.5, 3.5, 3.5], 1.0).count(), 7);
+    /// ```
+    pub fn ball_region(&self, center: [f64; D], radius: f64) -> EllipsoidRegion<D> {
+        EllipsoidRegion::new(self.size, center, [radius; D])
+    }
+
+    /// Returns the region of the axis-aligned ellipsoid with `center` and `radii`, clipped to the size of the buffer.
+    ///
+    /// The element at index `i` is centered at `i + 0.5` along each axis.
+    ///
+    /// バッファのサイズに切り詰められた、中心`center`と半径`radii`の軸に沿った楕円体の領域を返します。
+    ///
+    /// インデックス`i`の要素の中心は各軸に沿って`i + 0.5`にあります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 2>::new([8, 8], 0).unwrap();
+    ///
+    /// let region = buf.ellipsoid_region([4.0, 0.5], [2.0, 0.5]).collect::<Vec<_>>();
+    /// assert_eq!(region, vec![[2, 0], [3, 0], [4, 0], [5, 0]]);
+    /// ```
+    pub fn ellipsoid_region(&self, center: [f64; D], radii: [f64; D]) -> EllipsoidRegion<D> {
+        EllipsoidRegion::new(self.size, center, radii)
+    }
+}
+
+impl<T, S: Storage<T>> XDBuf<T, 2, S> {
+    /// Returns the region of the polygon with the vertices `points`, clipped to the size of the buffer.
+    ///
+    /// The element at index `i` is centered at `i + 0.5` along each axis.
+    ///
+    /// バッファのサイズに切り詰められた、頂点`points`を持つ多角形の領域を返します。
+    ///
+    /// インデックス`i`の要素の中心は各軸に沿って`i + 0.5`にあります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{FillRule, XDBuf};
+    ///
+    /// let buf = XDBuf::<u8, 2>::new([8, 8], 0).unwrap();
+    ///
+    /// let square = [[1.0, 1.0], [4.0, 1.0], [4.0, 4.0], [1.0, 4.0]];
+    /// assert_eq!(buf.polygon_region(&square, FillRule::EvenOdd).count(), 9);
+    /// ```
+    pub fn polygon_region(&self, points: &[[f64; 2]], rule: FillRule) -> PolygonRegion {
+        PolygonRegion::new(self.size, points, rule)
+    }
+
+    /// Returns the region of the triangle with the vertices `a`, `b` and `c`, clipped to the size of the buffer.
+    ///
+    /// バッファのサイズに切り詰められた、頂点`a`、`b`、`c`を持つ三角形の領域を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 2>::new([4, 4], 0).unwrap();
+    ///
+    /// let region = buf.triangle_region([0.0, 0.0], [4.0, 0.0], [0.0, 4.0]).count();
+    /// assert_eq!(region, 6);
+    /// ```
+    pub fn triangle_region(&self, a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> PolygonRegion {
+        PolygonRegion::new(self.size, &[a, b, c], FillRule::NonZero)
+    }
+}
+
+impl<T: Clone, const D: usize, S: StorageMut<T>> XDBuf<T, D, S> {
+    /// Sets `value` to every element of the box from `min` to `max`, both inclusive, clipped to the size of the buffer.
+    ///
+    /// バッファのサイズに切り詰められた、両端を含む`min`から`max`までの直方体のすべての要素に`value`を設定します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new([4, 3], 0).unwrap();
+    /// buf.fill_box([2, -1], [8, 1], 1);
+    ///
+    /// assert_eq!(buf.as_slice(), &[
+    ///     0, 0, 1, 1,
+    ///     0, 0, 1, 1,
+    ///     0, 0, 0, 0,
+    /// ]);
+    /// ```
+    pub fn fill_box(&mut self, min: [isize; D], max: [isize; D], value: T) {
+        let region = self.box_region(min, max);
+        self.fill_region(region, value);
+    }
+
+    /// Sets `value` to every element of the ball with `center` and `radius`, clipped to the size of the buffer.
+    ///
+    /// バッファのサイズに切り詰められた、中心`center`と半径`radius`の球のすべての要素に`value`を設定します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new([5, 5], 0).unwrap();
+    /// buf.fill_ball([2.5, 2.5], 1.5, 1);
+    ///
+    /// assert_eq!(buf.as_slice(), &[
+    ///     0, 0, 0, 0, 0,
+    ///     0, 1, 1, 1, 0,
+    ///     0, 1, 1, 1, 0,
+    ///     0, 1, 1, 1, 0,
+    ///     0, 0, 0, 0, 0,
+    /// ]);
+    /// ```
+    pub fn fill_ball(&mut self, center: [f64; D], radius: f64, value: T) {
+        let region = self.ball_region(center, radius);
+        self.fill_region(region, value);
+    }
+
+    /// Sets `value` to every element of the axis-aligned ellipsoid with `center` and `radii`, clipped to the size of the buffer.
+    ///
+    /// バッファのサイズに切り詰められた、中心`center`と半径`radii`の軸に沿った楕円体のすべての要素に`value`を設定します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new([6, 3], 0).unwrap();
+    /// buf.fill_ellipsoid([3.0, 1.5], [3.0, 1.2], 1);
+    ///
+    /// assert_eq!(buf.as_slice(), &[
+    ///     0, 1, 1, 1, 1, 0,
+    ///     1, 1, 1, 1, 1, 1,
+    ///     0, 1, 1, 1, 1, 0,
+    /// ]);
+    /// ```
+    pub fn fill_ellipsoid(&mut self, center: [f64; D], radii: [f64; D], value: T) {
+        let region = self.ellipsoid_region(center, radii);
+        self.fill_region(region, value);
+    }
+
+    /// Sets `value` to every element at the indices yielded by `region`, which must be in range.
+    ///
+    /// `region`が生成する範囲内のインデックスにあるすべての要素に`value`を設定します。
+    fn fill_region(&mut self, region: impl Iterator<Item = [usize; D]>, value: T) {
+        for index in region {
+            // 領域はバッファのサイズに切り詰められている
+            let scalar = self.to_scalar_index(&index).unwrap();
+            self.as_mut_slice()[scalar] = value.clone();
+        }
+    }
+}
+
+impl<T: Clone, S: StorageMut<T>> XDBuf<T, 2, S> {
+    /// Sets `value` to every element of the polygon with the vertices `points`, clipped to the size of the buffer.
+    ///
+    /// バッファのサイズに切り詰められた、頂点`points`を持つ多角形のすべての要素に`value`を設定します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{FillRule, XDBuf};
+    ///
+    /// // 自己交差する五芒星
+    /// let star = [[2.5, 0.0], [4.0, 5.0], [0.0, 1.8], [5.0, 1.8], [1.0, 5.0]];
+    ///
+    /// let mut nonzero = XDBuf::<u8, 2>::new([5, 5], 0).unwrap();
+    /// nonzero.fill_polygon(&star, FillRule::NonZero, 1);
+    ///
+    /// let mut even_odd = XDBuf::<u8, 2>::new([5, 5], 0).unwrap();
+    /// even_odd.fill_polygon(&star, FillRule::EvenOdd, 1);
+    ///
+    /// // 中央の五角形はNonZeroでのみ内側
+    /// assert_eq!(nonzero.get_m([2, 2]), Some(&1));
+    /// assert_eq!(even_odd.get_m([2, 2]), Some(&0));
+    /// ```
+    pub fn fill_polygon(&mut self, points: &[[f64; 2]], rule: FillRule, value: T) {
+        let region = self.polygon_region(points, rule);
+        self.fill_region(region, value);
+    }
+
+    /// Sets `value` to every element of the triangle with the vertices `a`, `b` and `c`, clipped to the size of the buffer.
+    ///
+    /// バッファのサイズに切り詰められた、頂点`a`、`b`、`c`を持つ三角形のすべての要素に`value`を設定します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new([4, 4], 0).unwrap();
+    /// buf.fill_triangle([0.0, 0.0], [4.0, 0.0], [4.0, 4.0], 1);
+    ///
+    /// assert_eq!(buf.as_slice(), &[
+    ///     1, 1, 1, 1,
+    ///     0, 1, 1, 1,
+    ///     0, 0, 1, 1,
+    ///     0, 0, 0, 1,
+    /// ]);
+    /// ```
+    pub fn fill_triangle(&mut self, a: [f64; 2], b: [f64; 2], c: [f64; 2], value: T) {
+        let region = self.triangle_region(a, b, c);
+        self.fill_region(region, value);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn box_region_is_clipped_or_empty() {
+        let buf = XDBuf::<u8, 3>::new([3, 4, 5], 0).unwrap();
+
+        assert_eq!(buf.box_region([-10, -10, -10], [10, 10, 10]).count(), 60);
+        assert_eq!(buf.box_region([1, 1, 1], [0, 3, 3]).count(), 0);
+        assert_eq!(buf.box_region([3, 0, 0], [5, 3, 4]).count(), 0);
+        assert_eq!(buf.box_region([2, 3, 4], [2, 3, 4]).collect::<Vec<_>>(), vec![[2, 3, 4]]);
+    }
+
+    #[test]
+    fn ball_region_matches_distance_test_in_4d() {
+        let buf = XDBuf::<u8, 4>::new([6, 6, 6, 6], 0).unwrap();
+        let center = [1.0, 2.5, 4.0, 3.2];
+        let radius = 2.3;
+
+        let region = buf.ball_region(center, radius).collect::<Vec<_>>();
+        let expected = buf.indexed_iter().map(|(i, _)| i).filter(|i| {
+            (0..4).map(|a| (i[a] as f64 + 0.5 - center[a]).powi(2)).sum::<f64>() <= radius * radius
+        }).collect::<Vec<_>>();
+
+        assert!(!expected.is_empty());
+        assert_eq!(region.len(), expected.len());
+        assert!(expected.iter().all(|i| region.contains(i)));
+    }
+
+    #[test]
+    fn polygon_rules_differ_on_overlapping_loops() {
+        let buf = XDBuf::<u8, 2>::new([6, 6], 0).unwrap();
+
+        // 同じ向きに2周する正方形
+        let square = [[1.0, 1.0], [5.0, 1.0], [5.0, 5.0], [1.0, 5.0]];
+        let twice = [square, square].concat();
+
+        assert_eq!(buf.polygon_region(&twice, FillRule::NonZero).count(), 16);
+        assert_eq!(buf.polygon_region(&twice, FillRule::EvenOdd).count(), 0);
+
+        // 切り詰められた多角形
+        let large = [[-3.0, -3.0], [9.0, -3.0], [9.0, 9.0], [-3.0, 9.0]];
+        assert_eq!(buf.polygon_region(&large, FillRule::EvenOdd).count(), 36);
+    }
+}
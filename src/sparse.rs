@@ -0,0 +1,517 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use anyhow::anyhow;
+
+use crate::layout::Layout;
+use crate::storage::Storage;
+use crate::walker::{Walkable, Walker};
+use crate::XDBuf;
+
+/// Structure representing an n-dimensional buffer that only stores cells differing from a default value
+///
+/// Memory usage is proportional to the number of occupied cells rather than the total product of `size`.
+/// Scalar indices are the same as for an `XDBuf` of the same size.
+///
+/// 既定値と異なるセルのみを格納するn次元のバッファを表す構造体です。
+///
+/// メモリ使用量は`size`の総積ではなく、占有されているセルの数に比例します。
+/// スカラーのインデックスは同じサイズの`XDBuf`と同じです。
+#[derive(Debug, Clone)]
+pub struct SparseXDBuf<T, const D: usize> {
+    cells: HashMap<usize, T>,
+    size: [usize; D],
+    stride: [usize; D],
+    len: usize,
+    default: T,
+}
+
+impl<T, const D: usize> SparseXDBuf<T, D> {
+    /// Generate a new, empty `SparseXDBuf`.
+    ///
+    /// Every cell reads as `default` until it is set to a different value.
+    ///
+    /// 新しい空の`SparseXDBuf`を生成します。
+    ///
+    /// 各セルは異なる値が設定されるまで`default`として読み出されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::SparseXDBuf;
+    ///
+    /// let buf = SparseXDBuf::<i32, 3>::new([1000, 1000, 1000], 0).unwrap();
+    ///
+    /// assert_eq!(buf.len(), 1_000_000_000);
+    /// assert_eq!(buf.occupied_len(), 0);
+    /// assert_eq!(buf.get_m([999, 999, 999]), Some(&0));
+    /// ```
+    pub fn new(size: [usize; D], default: T) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            cells: HashMap::new(),
+            size,
+            stride: XDBuf::<T, D>::calc_dim_stride(&size)?,
+            len: XDBuf::<T, D>::calc_total_size(&size)?,
+            default,
+        })
+    }
+
+    /// Generate a `SparseXDBuf` holding the cells of `buf` that differ from `default`.
+    ///
+    /// `buf`のうち`default`と異なるセルを保持する`SparseXDBuf`を生成します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{SparseXDBuf, XDBuf};
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([3, 3], vec![0, 0, 1, 0, 0, 0, 2, 0, 0]).unwrap();
+    /// let sparse = SparseXDBuf::from_xdbuf(&buf, 0);
+    ///
+    /// assert_eq!(sparse.occupied_len(), 2);
+    /// assert_eq!(sparse.get_m([0, 2]), Some(&2));
+    /// ```
+    pub fn from_xdbuf<S: Storage<T>>(buf: &XDBuf<T, D, S>, default: T) -> Self
+    where
+        T: Clone + PartialEq,
+    {
+        // 既存の`XDBuf`のサイズは検証済みなので、ストライドの計算は失敗しない
+        let stride = XDBuf::<T, D>::calc_dim_stride_with_layout(&buf.size, Layout::FirstMajor).unwrap();
+
+        let cells = buf.indexed_iter()
+            .filter(|(_, v)| **v != default)
+            .map(|(index, v)| (index.iter().zip(stride).map(|(&i, s)| i * s).sum(), v.clone()))
+            .collect();
+
+        Self {
+            cells,
+            size: buf.size,
+            stride,
+            len: buf.len(),
+            default,
+        }
+    }
+
+    /// Generate a dense `XDBuf` with the same size and contents as this buffer.
+    ///
+    /// このバッファと同じサイズと内容を持つ密な`XDBuf`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the memory for the dense buffer cannot be described by `usize`.
+    ///
+    /// * 密なバッファのメモリ量が`usize`で表現できない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::SparseXDBuf;
+    ///
+    /// let mut sparse = SparseXDBuf::<i32, 2>::new([3, 3], 0).unwrap();
+    /// sparse.set_m([1, 1], 5).unwrap();
+    ///
+    /// let buf = sparse.to_xdbuf().unwrap();
+    /// assert_eq!(buf.get_m([1, 1]), Some(&5));
+    /// assert_eq!(buf.get_m([0, 0]), Some(&0));
+    /// ```
+    pub fn to_xdbuf(&self) -> Result<XDBuf<T, D>, anyhow::Error>
+    where
+        T: Clone,
+    {
+        let mut buf = XDBuf::new(self.size, self.default.clone())?;
+
+        for (&scalar, value) in &self.cells {
+            buf.buf[scalar] = value.clone();
+        }
+
+        Ok(buf)
+    }
+
+    /// Converts into a dense `XDBuf` if the ratio of occupied cells is at least `threshold`.
+    ///
+    /// Otherwise, returns the buffer unchanged as `Err`.
+    ///
+    /// 占有されているセルの割合が`threshold`以上の場合、密な`XDBuf`に変換します。
+    ///
+    /// そうでない場合は、バッファをそのまま`Err`として返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::SparseXDBuf;
+    ///
+    /// let mut sparse = SparseXDBuf::<i32, 2>::new([2, 2], 0).unwrap();
+    /// sparse.set_m([0, 0], 1).unwrap();
+    ///
+    /// let sparse = sparse.try_into_dense(0.5).unwrap_err();
+    /// assert_eq!(sparse.density(), 0.25);
+    ///
+    /// let buf = sparse.try_into_dense(0.25).unwrap();
+    /// assert_eq!(buf.get(0), Some(&1));
+    /// ```
+    pub fn try_into_dense(self, threshold: f64) -> Result<XDBuf<T, D>, Self>
+    where
+        T: Clone,
+    {
+        if self.density() < threshold {
+            return Err(self);
+        }
+
+        self.to_xdbuf().map_err(|_| self)
+    }
+
+    /// Returns the ratio of occupied cells to all cells.
+    ///
+    /// 全セルに対する占有されているセルの割合を返します。
+    pub fn density(&self) -> f64 {
+        self.cells.len() as f64 / self.len as f64
+    }
+
+    /// Returns the number of occupied cells.
+    ///
+    /// 占有されているセルの数を返します。
+    pub fn occupied_len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns the value of unoccupied cells.
+    ///
+    /// 占有されていないセルの値を返します。
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    /// Convert an index in array notation to a scalar index.
+    ///
+    /// 配列表記のインデックスをスカラーのインデックスに変換します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    pub fn to_scalar_index(&self, index: &[usize; D]) -> Result<usize, anyhow::Error> {
+        let in_range = index.iter().zip(self.size.iter()).all(|(&i, &s)| i < s);
+
+        if !in_range {
+            return Err(anyhow!("index is out of range"));
+        }
+
+        Ok(index.iter().zip(self.stride).map(|(&i, s)| i * s).sum())
+    }
+
+    /// Convert scalar index to array notation.
+    ///
+    /// スカラーのインデックスを配列表記に変換します。
+    pub fn to_mul_dim_index(&self, mut scalar: usize) -> [usize; D] {
+        let mut index = [0; D];
+
+        for i in (0..D).rev() {
+            index[i] = scalar / self.stride[i];
+            scalar %= self.stride[i];
+        }
+
+        index
+    }
+
+    /// Get a reference to the element specified by the scalar `index`.
+    ///
+    /// Returns the default value for unoccupied cells, and `None` if `index` is out of range.
+    ///
+    /// スカラーの`index`で指定された要素の参照を取得します。
+    ///
+    /// 占有されていないセルでは既定値を、`index`が範囲外の場合は`None`を返します。
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        Some(self.cells.get(&index).unwrap_or(&self.default))
+    }
+
+    /// Get a reference to the element specified by `index` in array notation.
+    ///
+    /// Returns the default value for unoccupied cells, and `None` if `index` is out of range.
+    ///
+    /// 配列表記の`index`で指定された要素の参照を取得します。
+    ///
+    /// 占有されていないセルでは既定値を、`index`が範囲外の場合は`None`を返します。
+    pub fn get_m(&self, index: [usize; D]) -> Option<&T> {
+        self.get(self.to_scalar_index(&index).ok()?)
+    }
+
+    /// Set `value` to the element specified by the scalar `index`.
+    ///
+    /// Setting the default value releases the cell.
+    ///
+    /// スカラーの`index`で指定された要素に`value`を設定します。
+    ///
+    /// 既定値を設定するとセルは解放されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    pub fn set(&mut self, index: usize, value: T) -> Result<(), anyhow::Error>
+    where
+        T: PartialEq,
+    {
+        if index >= self.len {
+            return Err(anyhow!("index is out of range"));
+        }
+
+        if value == self.default {
+            self.cells.remove(&index);
+        } else {
+            self.cells.insert(index, value);
+        }
+
+        Ok(())
+    }
+
+    /// Set `value` to the element specified by `index` in array notation.
+    ///
+    /// Setting the default value releases the cell.
+    ///
+    /// 配列表記の`index`で指定された要素に`value`を設定します。
+    ///
+    /// 既定値を設定するとセルは解放されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::SparseXDBuf;
+    ///
+    /// let mut buf = SparseXDBuf::<i32, 2>::new([3, 3], 0).unwrap();
+    ///
+    /// buf.set_m([1, 2], 5).unwrap();
+    /// assert_eq!(buf.occupied_len(), 1);
+    ///
+    /// buf.set_m([1, 2], 0).unwrap();
+    /// assert_eq!(buf.occupied_len(), 0);
+    /// ```
+    pub fn set_m(&mut self, index: [usize; D], value: T) -> Result<(), anyhow::Error>
+    where
+        T: PartialEq,
+    {
+        let scalar = self.to_scalar_index(&index)?;
+
+        self.set(scalar, value)
+    }
+
+    /// Releases the cell specified by the scalar `index`, returning its value if it was occupied.
+    ///
+    /// スカラーの`index`で指定されたセルを解放し、占有されていた場合はその値を返します。
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.cells.remove(&index)
+    }
+
+    /// Releases all cells, keeping the size.
+    ///
+    /// サイズを保ったまま、すべてのセルを解放します。
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Returns an iterator over the occupied cells together with their indices in array notation.
+    ///
+    /// The order of iteration is unspecified.
+    ///
+    /// 占有されているセルを配列表記のインデックスと共に走査するイテレータを返します。
+    ///
+    /// 走査の順序は規定されません。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::SparseXDBuf;
+    ///
+    /// let mut buf = SparseXDBuf::<i32, 2>::new([3, 3], 0).unwrap();
+    /// buf.set_m([1, 2], 5).unwrap();
+    ///
+    /// assert_eq!(buf.iter_occupied().collect::<Vec<_>>(), vec![([1, 2], &5)]);
+    /// ```
+    pub fn iter_occupied(&self) -> impl Iterator<Item = ([usize; D], &T)> + '_ {
+        self.cells.iter().map(move |(&scalar, v)| (self.to_mul_dim_index(scalar), v))
+    }
+
+    /// Generates a `Walker` with the specified `index` as its initial position.
+    ///
+    /// 指定された`index`を初期位置として`Walker`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{step2d, SparseXDBuf};
+    ///
+    /// let mut buf = SparseXDBuf::<i32, 2>::new([100, 100], 0).unwrap();
+    /// buf.set_m([50, 60], 1).unwrap();
+    ///
+    /// let mut walker = buf.walker_from_m([50, 50]).unwrap();
+    /// walker.as_until(|&v, _| v != 0).unwrap();
+    /// assert_eq!(walker.index_m(), [50, 60]);
+    ///
+    /// walker.as_(&step2d::UP).unwrap();
+    /// assert_eq!(buf.get(walker.index_s()), Some(&0));
+    /// ```
+    pub fn walker_from_m(&self, index: [usize; D]) -> Result<Walker<'_, T, D, Self>, anyhow::Error> {
+        let scalar = self.to_scalar_index(&index)?;
+
+        self.walker_from_s(scalar)
+    }
+
+    /// Generates a `Walker` with the specified `scalar_index` as its initial position.
+    ///
+    /// 指定された`scalar_index`を初期位置として`Walker`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `scalar_index` is out of range.
+    ///
+    /// * `scalar_index`が範囲外の場合エラーになります。
+    pub fn walker_from_s(&self, scalar_index: usize) -> Result<Walker<'_, T, D, Self>, anyhow::Error> {
+        if scalar_index >= self.len {
+            return Err(anyhow!("index is out of range"));
+        }
+
+        Ok(Walker::new(self, scalar_index))
+    }
+
+    /// Returns the number of elements in the buffer, including unoccupied cells.
+    ///
+    /// 占有されていないセルを含む、バッファの要素数を返します。
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer contains no elements.
+    ///
+    /// バッファが要素を持たない場合に`true`を返します。
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a range of buffer indices.
+    ///
+    /// バッファのインデックスの範囲を返します。
+    pub fn idx_range(&self) -> Range<usize> {
+        0..self.len
+    }
+
+    /// Returns the size of each dimension of the buffer.
+    ///
+    /// バッファの各次元のサイズを返します。
+    pub fn size(&self) -> [usize; D] {
+        self.size
+    }
+}
+
+impl<T: Clone + PartialEq, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Converts into a `SparseXDBuf` if the ratio of cells differing from `default` is below `threshold`.
+    ///
+    /// Otherwise, returns the buffer unchanged as `Err`.
+    ///
+    /// `default`と異なるセルの割合が`threshold`未満の場合、`SparseXDBuf`に変換します。
+    ///
+    /// そうでない場合は、バッファをそのまま`Err`として返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![0, 0, 0, 1]).unwrap();
+    ///
+    /// let buf = buf.try_into_sparse(0, 0.25).unwrap_err();
+    /// let sparse = buf.try_into_sparse(0, 0.5).unwrap();
+    ///
+    /// assert_eq!(sparse.occupied_len(), 1);
+    /// ```
+    pub fn try_into_sparse(self, default: T, threshold: f64) -> Result<SparseXDBuf<T, D>, Self> {
+        let occupied = self.iter().filter(|&v| *v != default).count();
+
+        if occupied as f64 / self.len() as f64 >= threshold {
+            return Err(self);
+        }
+
+        Ok(SparseXDBuf::from_xdbuf(&self, default))
+    }
+}
+
+impl<T, const D: usize> Walkable<T, D> for SparseXDBuf<T, D> {
+    fn size(&self) -> [usize; D] {
+        self.size
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        SparseXDBuf::get(self, index)
+    }
+
+    fn to_scalar_index(&self, index: &[usize; D]) -> Result<usize, anyhow::Error> {
+        SparseXDBuf::to_scalar_index(self, index)
+    }
+
+    fn to_mul_dim_index(&self, scalar: usize) -> [usize; D] {
+        SparseXDBuf::to_mul_dim_index(self, scalar)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_xdbuf_in_any_layout() {
+        let mut buf = XDBuf::<i32, 3>::new_with_layout([3, 4, 5], 0, Layout::LastMajor).unwrap();
+        buf.set_m([2, 1, 3], 7).unwrap();
+        buf.set_m([0, 3, 4], 9).unwrap();
+
+        let sparse = SparseXDBuf::from_xdbuf(&buf, 0);
+        assert_eq!(sparse.occupied_len(), 2);
+
+        let dense = sparse.to_xdbuf().unwrap();
+        for (index, value) in buf.indexed_iter() {
+            assert_eq!(dense.get_m(index), Some(value));
+        }
+    }
+
+    #[test]
+    fn walker_matches_dense_walker() {
+        let mut sparse = SparseXDBuf::<i32, 2>::new([4, 4], 0).unwrap();
+        sparse.set_m([3, 1], 2).unwrap();
+        let dense = sparse.to_xdbuf().unwrap();
+
+        let mut s = sparse.walker_from_m([1, 1]).unwrap();
+        let mut d = dense.walker_from_m([1, 1]).unwrap();
+
+        s.as_(&[1, 0]).unwrap().as_until(|&v, _| v == 2).unwrap();
+        d.as_(&[1, 0]).unwrap().as_until(|&v, _| v == 2).unwrap();
+        assert_eq!(s.index_s(), d.index_s());
+
+        assert!(s.index_(&[1, 0]).is_err());
+        assert!(sparse.walker_from_m([1, 1]).unwrap().index_until(|&v, _| v == 3).is_err());
+    }
+}
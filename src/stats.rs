@@ -0,0 +1,457 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+use anyhow::anyhow;
+use num_traits::ToPrimitive;
+
+use crate::storage::Storage;
+use crate::xdbuf::to_mul_dim_index;
+use crate::XDBuf;
+
+/// Summary statistics of the values of a buffer or of a masked region
+///
+/// バッファまたはマスクされた領域の値の要約統計量
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats<T> {
+    /// The number of values.
+    ///
+    /// 値の個数。
+    pub count: usize,
+    /// The smallest value.
+    ///
+    /// 最小の値。
+    pub min: T,
+    /// The largest value.
+    ///
+    /// 最大の値。
+    pub max: T,
+    /// The arithmetic mean.
+    ///
+    /// 算術平均。
+    pub mean: f64,
+    /// The population variance.
+    ///
+    /// 母分散。
+    pub variance: f64,
+}
+
+impl<T> Stats<T> {
+    /// Returns the population standard deviation.
+    ///
+    /// 母標準偏差を返します。
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+}
+
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Returns the summary statistics of all the elements.
+    ///
+    /// Values that are not comparable with themselves, such as NaN, are ignored and not counted.
+    ///
+    /// すべての要素の要約統計量を返します。
+    ///
+    /// NaNなど、自身と比較できない値は無視され、数えられません。
+    ///
+    /// # Errors
+    ///
+    /// * Error if no element is comparable, such as when all elements are NaN.
+    ///
+    /// * すべての要素がNaNである場合など、比較できる要素がない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![2, 4, 4, 6]).unwrap();
+    /// let stats = buf.stats().unwrap();
+    ///
+    /// assert_eq!((stats.count, stats.min, stats.max), (4, 2, 6));
+    /// assert_eq!(stats.mean, 4.0);
+    /// assert_eq!(stats.variance, 2.0);
+    /// ```
+    pub fn stats(&self) -> Result<Stats<T>, anyhow::Error>
+    where
+        T: Copy + PartialOrd + ToPrimitive,
+    {
+        stats(self.iter()).ok_or_else(|| anyhow!("no element is comparable"))
+    }
+
+    /// Returns the summary statistics of the elements where `mask` is `true`.
+    ///
+    /// Returns `None` if `mask` selects no comparable element. Values that are not comparable with themselves, such as NaN, are ignored.
+    ///
+    /// `mask`が`true`である要素の要約統計量を返します。
+    ///
+    /// `mask`が比較できる要素を1つも選択しない場合は`None`を返します。NaNなど、自身と比較できない値は無視されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the sizes of the buffer and `mask` differ.
+    ///
+    /// * バッファと`mask`のサイズが異なる場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<f32, 1>::new_with_vec([4], vec![1.0, 9.0, 3.0, 5.0]).unwrap();
+    /// let mask = buf.map(|&v| v < 6.0);
+    ///
+    /// let stats = buf.stats_masked(&mask).unwrap().unwrap();
+    /// assert_eq!((stats.min, stats.max, stats.mean), (1.0, 5.0, 3.0));
+    /// ```
+    pub fn stats_masked<S2: Storage<bool>>(&self, mask: &XDBuf<bool, D, S2>) -> Result<Option<Stats<T>>, anyhow::Error>
+    where
+        T: Copy + PartialOrd + ToPrimitive,
+    {
+        Ok(stats(self.masked(mask)?))
+    }
+
+    /// Counts the elements falling into each of `bins` bins of equal width spanning `range`.
+    ///
+    /// The last bin includes the upper end of `range`. Elements outside `range` are not counted.
+    ///
+    /// `range`を等しい幅で分割した`bins`個の区間に入る要素を数えます。
+    ///
+    /// 最後の区間は`range`の上端を含みます。`range`の外側の要素は数えられません。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `bins` is zero.
+    /// * Error if `range` is empty or not finite.
+    ///
+    /// * `bins`が0の場合エラーになります。
+    /// * `range`が空または有限でない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<f64, 1>::new_with_vec([6], vec![0.0, 0.1, 0.5, 0.9, 1.0, 2.0]).unwrap();
+    ///
+    /// assert_eq!(buf.histogram(2, 0.0..=1.0).unwrap(), vec![2, 3]);
+    /// ```
+    pub fn histogram(&self, bins: usize, range: RangeInclusive<f64>) -> Result<Vec<usize>, anyhow::Error>
+    where
+        T: ToPrimitive,
+    {
+        histogram(self.iter(), bins, range)
+    }
+
+    /// Counts the elements where `mask` is `true` falling into each of `bins` bins of equal width spanning `range`.
+    ///
+    /// `mask`が`true`である要素のうち、`range`を等しい幅で分割した`bins`個の区間に入るものを数えます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the sizes of the buffer and `mask` differ.
+    /// * Error if `bins` is zero.
+    /// * Error if `range` is empty or not finite.
+    ///
+    /// * バッファと`mask`のサイズが異なる場合エラーになります。
+    /// * `bins`が0の場合エラーになります。
+    /// * `range`が空または有限でない場合エラーになります。
+    pub fn histogram_masked<S2: Storage<bool>>(
+        &self,
+        mask: &XDBuf<bool, D, S2>,
+        bins: usize,
+        range: RangeInclusive<f64>,
+    ) -> Result<Vec<usize>, anyhow::Error>
+    where
+        T: ToPrimitive,
+    {
+        histogram(self.masked(mask)?, bins, range)
+    }
+
+    /// Counts the occurrences of each distinct value.
+    ///
+    /// 異なる値ごとに出現回数を数えます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let tiles = XDBuf::<char, 2>::new_with_vec([3, 2], vec!['#', '.', '.', '#', '.', '~']).unwrap();
+    /// let counts = tiles.value_counts();
+    ///
+    /// assert_eq!(counts[&'.'], 3);
+    /// assert_eq!(counts[&'#'], 2);
+    /// assert_eq!(counts[&'~'], 1);
+    /// ```
+    pub fn value_counts(&self) -> HashMap<&T, usize>
+    where
+        T: Eq + Hash,
+    {
+        value_counts(self.iter())
+    }
+
+    /// Counts the occurrences of each distinct value where `mask` is `true`.
+    ///
+    /// `mask`が`true`である要素について、異なる値ごとに出現回数を数えます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the sizes of the buffer and `mask` differ.
+    ///
+    /// * バッファと`mask`のサイズが異なる場合エラーになります。
+    pub fn value_counts_masked<S2: Storage<bool>>(&self, mask: &XDBuf<bool, D, S2>) -> Result<HashMap<&T, usize>, anyhow::Error>
+    where
+        T: Eq + Hash,
+    {
+        Ok(value_counts(self.masked(mask)?))
+    }
+
+    /// Returns the `p`-th percentile of the elements by the nearest-rank method.
+    ///
+    /// The result is the smallest element such that at least `p` percent of the elements are less than or equal to it,
+    /// that is, the `ceil(p / 100 * n)`-th smallest of the `n` elements, or the smallest one if `p` is 0.
+    /// Values that are not comparable with themselves, such as NaN, are ignored.
+    /// The element is found by selection in linear time on a copy of the values, without sorting them.
+    ///
+    /// 最近順位法により要素の第`p`百分位数を返します。
+    ///
+    /// 結果は、要素の少なくとも`p`パーセントがそれ以下となる最小の要素、すなわち`n`個の要素のうち`ceil(p / 100 * n)`番目に小さいものです。
+    /// `p`が0の場合は最小の要素になります。NaNなど、自身と比較できない値は無視されます。
+    /// 要素は値の複製に対する線形時間の選択で求められ、並べ替えは行われません。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `p` is not between 0 and 100.
+    /// * Error if no element is comparable, such as when all elements are NaN.
+    ///
+    /// * `p`が0から100の間にない場合エラーになります。
+    /// * すべての要素がNaNである場合など、比較できる要素がない場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 1>::new_with_vec([5], vec![50, 10, 40, 20, 30]).unwrap();
+    ///
+    /// assert_eq!(buf.percentile(0.0).unwrap(), 10);
+    /// assert_eq!(buf.percentile(50.0).unwrap(), 30);
+    /// assert_eq!(buf.percentile(100.0).unwrap(), 50);
+    ///
+    /// // 偶数個の場合、中央値は中央の2つのうち小さい方になる
+    /// let buf = XDBuf::<i32, 1>::new_with_vec([4], vec![40, 10, 30, 20]).unwrap();
+    ///
+    /// assert_eq!(buf.percentile(25.0).unwrap(), 10);
+    /// assert_eq!(buf.percentile(50.0).unwrap(), 20);
+    /// assert_eq!(buf.percentile(50.1).unwrap(), 30);
+    /// assert_eq!(buf.percentile(75.0).unwrap(), 30);
+    /// ```
+    pub fn percentile(&self, p: f64) -> Result<T, anyhow::Error>
+    where
+        T: Copy + PartialOrd,
+    {
+        percentile(self.iter(), p)?.ok_or_else(|| anyhow!("no element is comparable"))
+    }
+
+    /// Returns the `p`-th percentile of the elements where `mask` is `true` by the nearest-rank method.
+    ///
+    /// Returns `None` if `mask` selects no comparable element. Values that are not comparable with themselves, such as NaN, are ignored.
+    ///
+    /// 最近順位法により`mask`が`true`である要素の第`p`百分位数を返します。
+    ///
+    /// `mask`が比較できる要素を1つも選択しない場合は`None`を返します。NaNなど、自身と比較できない値は無視されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the sizes of the buffer and `mask` differ.
+    /// * Error if `p` is not between 0 and 100.
+    ///
+    /// * バッファと`mask`のサイズが異なる場合エラーになります。
+    /// * `p`が0から100の間にない場合エラーになります。
+    pub fn percentile_masked<S2: Storage<bool>>(&self, mask: &XDBuf<bool, D, S2>, p: f64) -> Result<Option<T>, anyhow::Error>
+    where
+        T: Copy + PartialOrd,
+    {
+        percentile(self.masked(mask)?, p)
+    }
+
+    /// Returns an iterator over the elements where `mask` is `true`.
+    ///
+    /// The memory layouts of the buffer and `mask` may differ.
+    ///
+    /// `mask`が`true`である要素を走査するイテレータを返します。
+    ///
+    /// バッファと`mask`のメモリ配置は異なっていても構いません。
+    fn masked<'a: 'b, 'b, S2: Storage<bool>>(&'a self, mask: &'b XDBuf<bool, D, S2>) -> Result<impl Iterator<Item = &'a T> + 'b, anyhow::Error> {
+        if self.size != mask.size {
+            return Err(anyhow!("shape mismatch: {:?} and {:?} differ", self.size, mask.size));
+        }
+
+        Ok(self.iter().enumerate().filter_map(move |(i, v)| {
+            let m = if self.layout == mask.layout {
+                i
+            } else {
+                let index = to_mul_dim_index(i, &self.stride, self.layout);
+                index.iter().zip(mask.stride).map(|(&i, s)| i * s).sum()
+            };
+
+            mask.as_slice()[m].then_some(v)
+        }))
+    }
+}
+
+fn stats<'a, T: Copy + PartialOrd + ToPrimitive + 'a>(values: impl Iterator<Item = &'a T>) -> Option<Stats<T>> {
+    // NaNのように自身と比較できない値は、最小値と最大値の初期値にも平均にも含めない
+    let mut values = values.filter(|v| v.partial_cmp(v).is_some()).peekable();
+    let &first = *values.peek()?;
+
+    let mut stats = Stats {
+        count: 0,
+        min: first,
+        max: first,
+        mean: 0.0,
+        variance: 0.0,
+    };
+
+    // Welfordの方法で平均と分散を逐次計算する
+    let mut m2 = 0.0;
+    for &v in values {
+        if v < stats.min {
+            stats.min = v;
+        }
+        if v > stats.max {
+            stats.max = v;
+        }
+
+        let x = v.to_f64().unwrap_or(f64::NAN);
+        stats.count += 1;
+        let delta = x - stats.mean;
+        stats.mean += delta / stats.count as f64;
+        m2 += delta * (x - stats.mean);
+    }
+    stats.variance = m2 / stats.count as f64;
+
+    Some(stats)
+}
+
+fn histogram<'a, T: ToPrimitive + 'a>(values: impl Iterator<Item = &'a T>, bins: usize, range: RangeInclusive<f64>) -> Result<Vec<usize>, anyhow::Error> {
+    let (lo, hi) = range.into_inner();
+    if bins == 0 {
+        return Err(anyhow!("bins must not be zero"));
+    }
+    if !(lo.is_finite() && hi.is_finite() && lo < hi) {
+        return Err(anyhow!("range must be finite and non-empty"));
+    }
+
+    let mut counts = vec![0; bins];
+    for v in values {
+        let x = v.to_f64().unwrap_or(f64::NAN);
+        if (lo..=hi).contains(&x) {
+            let bin = ((x - lo) / (hi - lo) * bins as f64) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+    }
+
+    Ok(counts)
+}
+
+fn value_counts<'a, T: Eq + Hash>(values: impl Iterator<Item = &'a T>) -> HashMap<&'a T, usize> {
+    let mut counts = HashMap::new();
+    for v in values {
+        *counts.entry(v).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+fn percentile<'a, T: Copy + PartialOrd + 'a>(values: impl Iterator<Item = &'a T>, p: f64) -> Result<Option<T>, anyhow::Error> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err(anyhow!("p must be between 0 and 100"));
+    }
+
+    // NaNのように自身と比較できない値を除き、比較が全順序になるようにする
+    let mut values = values.copied().filter(|v| v.partial_cmp(v).is_some()).collect::<Vec<_>>();
+    if values.is_empty() {
+        return Ok(None);
+    }
+
+    // 最近順位法の順位は1から数えるので、0番目の百分位数は最小の要素とする
+    let rank = (p * values.len() as f64 / 100.0).ceil() as usize;
+    let (_, &mut v, _) = values.select_nth_unstable_by(rank.max(1) - 1, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    Ok(Some(v))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Layout;
+
+    use super::*;
+
+    #[test]
+    fn mask_with_different_layout_selects_same_indices() {
+        let buf = XDBuf::<u32, 2>::new([3, 4], 0).unwrap().map_indexed(|[x, y], _| (x + 10 * y) as u32);
+        let mask = XDBuf::<bool, 2>::new_with_layout([3, 4], false, Layout::LastMajor).unwrap()
+            .map_indexed(|[x, y], _| x == 1 && y >= 2);
+
+        let stats = buf.stats_masked(&mask).unwrap().unwrap();
+        assert_eq!((stats.count, stats.min, stats.max), (2, 21, 31));
+        assert_eq!(buf.percentile_masked(&mask, 100.0).unwrap(), Some(31));
+        assert_eq!(buf.value_counts_masked(&mask).unwrap().len(), 2);
+        assert_eq!(buf.histogram_masked(&mask, 4, 0.0..=40.0).unwrap(), vec![0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn empty_mask_and_size_mismatch() {
+        let buf = XDBuf::<f64, 2>::new([2, 2], 1.0).unwrap();
+        let none = XDBuf::<bool, 2>::new([2, 2], false).unwrap();
+        let wrong = XDBuf::<bool, 2>::new([2, 3], true).unwrap();
+
+        assert_eq!(buf.stats_masked(&none).unwrap(), None);
+        assert_eq!(buf.percentile_masked(&none, 50.0).unwrap(), None);
+        assert!(buf.stats_masked(&wrong).is_err());
+        assert!(buf.histogram_masked(&wrong, 1, 0.0..=1.0).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        let buf = XDBuf::<f32, 1>::new_with_vec([3], vec![3.0, 1.0, 2.0]).unwrap();
+
+        assert!(buf.percentile(-1.0).is_err());
+        assert!(buf.percentile(f64::NAN).is_err());
+        assert!(buf.histogram(0, 0.0..=1.0).is_err());
+        assert!(buf.histogram(4, 1.0..=1.0).is_err());
+        assert_eq!(buf.percentile(50.0).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn stats_ignore_nan_wherever_it_is() {
+        for values in [vec![f64::NAN, 1.0, 5.0], vec![1.0, f64::NAN, 5.0], vec![1.0, 5.0, f64::NAN]] {
+            let stats = XDBuf::<f64, 1>::new_with_vec([3], values).unwrap().stats().unwrap();
+
+            assert_eq!((stats.count, stats.min, stats.max), (2, 1.0, 5.0));
+            assert_eq!((stats.mean, stats.variance), (3.0, 4.0));
+        }
+
+        let buf = XDBuf::<f32, 2>::new([2, 2], f32::NAN).unwrap();
+        assert!(buf.stats().is_err());
+        assert_eq!(buf.stats_masked(&buf.map(|_| true)).unwrap(), None);
+    }
+
+    #[test]
+    fn percentile_ignores_nan() {
+        let buf = XDBuf::<f64, 1>::new_with_vec([7], vec![f64::NAN, 3.0, f64::NAN, 1.0, 4.0, f64::NAN, 2.0]).unwrap();
+
+        assert_eq!(buf.percentile(0.0).unwrap(), 1.0);
+        assert_eq!(buf.percentile(50.0).unwrap(), 2.0);
+        assert_eq!(buf.percentile(100.0).unwrap(), 4.0);
+
+        let mask = buf.map(|v| v.is_nan());
+        assert_eq!(buf.percentile_masked(&mask, 50.0).unwrap(), None);
+
+        let all_nan = XDBuf::<f32, 2>::new([3, 3], f32::NAN).unwrap();
+        assert!(all_nan.percentile(50.0).is_err());
+    }
+}
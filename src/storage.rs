@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+/// Trait for contiguous memory that an `XDBuf` can read its elements from.
+///
+/// `XDBuf`が要素を読み出す連続したメモリを表すトレイトです。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::XDBuf;
+///
+/// let data = vec![1, 2, 3, 4, 5, 6].into_boxed_slice();
+/// let buf = XDBuf::<i32, 2, Box<[i32]>>::from_storage([3, 2], data).unwrap();
+///
+/// let walker = buf.walker_from_m([1, 1]).unwrap();
+/// assert_eq!(buf.get(walker.index_(&[1, 0]).unwrap()), Some(&6));
+/// ```
+///
+/// Only `Vec<T>` can be reinitialized.
+///
+/// `Vec<T>`のみ再初期化できます。
+///
+/// ```compile_fail
+/// use xdbuf::XDBuf;
+///
+/// let data = vec![0; 6].into_boxed_slice();
+/// let mut buf = XDBuf::<i32, 2, Box<[i32]>>::from_storage([3, 2], data).unwrap();
+///
+/// buf.init([2, 2], 0).unwrap(); // compile error!
+/// ```
+pub trait Storage<T> {
+    /// Returns the elements as a slice.
+    ///
+    /// 要素をスライスとして返します。
+    fn as_slice(&self) -> &[T];
+}
+
+/// Trait for contiguous memory whose elements an `XDBuf` can also modify.
+///
+/// `XDBuf`が要素を変更することもできる連続したメモリを表すトレイトです。
+pub trait StorageMut<T>: Storage<T> {
+    /// Returns the elements as a mutable slice.
+    ///
+    /// 要素を可変なスライスとして返します。
+    fn as_mut_slice(&mut self) -> &mut [T];
+}
+
+impl<T> Storage<T> for Vec<T> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> StorageMut<T> for Vec<T> {
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T> Storage<T> for Box<[T]> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> StorageMut<T> for Box<[T]> {
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T> Storage<T> for &[T] {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> Storage<T> for &mut [T] {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> StorageMut<T> for &mut [T] {
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T> Storage<T> for Arc<[T]> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::XDBuf;
+
+    use super::*;
+
+    #[test]
+    fn mutable_slice_writes_through() {
+        let mut data = vec![0_u8; 12];
+
+        {
+            let mut buf = XDBuf::<u8, 2, &mut [u8]>::from_storage([4, 3], &mut data).unwrap();
+            buf.set_m([3, 2], 7).unwrap();
+            buf.iter_mut().for_each(|v| *v += 1);
+        }
+
+        assert_eq!(data[11], 8);
+        assert_eq!(data[0], 1);
+    }
+
+    #[test]
+    fn shared_storage_is_readable_by_walker() {
+        let data: Arc<[i32]> = (1..=9).collect::<Vec<_>>().into();
+        let buf = XDBuf::<i32, 2, Arc<[i32]>>::from_storage([3, 3], data.clone()).unwrap();
+        let buf2 = XDBuf::<i32, 2, Arc<[i32]>>::from_storage([3, 3], data).unwrap();
+
+        let mut walker = buf.walker_from_m([0, 0]).unwrap();
+        walker.as_until(|&v, _| v == 5).unwrap();
+        assert_eq!(walker.index_m(), [1, 1]);
+        assert_eq!(buf2.get(walker.index_s()), Some(&5));
+    }
+
+    #[test]
+    fn from_storage_rejects_length_mismatch() {
+        let data = [0; 5];
+        assert!(XDBuf::<i32, 2, &[i32]>::from_storage([3, 2], &data[..]).is_err());
+    }
+}
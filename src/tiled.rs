@@ -0,0 +1,685 @@
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use anyhow::anyhow;
+
+use crate::layout::Layout;
+use crate::storage::Storage;
+use crate::walker::{Walkable, Walker};
+use crate::XDBuf;
+
+/// Structure representing an n-dimensional buffer stored as contiguous tiles of `TILE^D` elements
+///
+/// Elements that are close in every dimension are also close in memory, which makes neighbor access along any axis cheap.
+/// Indices are the same as for an `XDBuf` of the same size; only the memory layout differs.
+///
+/// `TILE^D`個の要素からなるタイルを連続して格納するn次元のバッファを表す構造体です。
+///
+/// どの次元についても近い要素がメモリ上でも近くに配置されるため、任意の軸方向の隣接アクセスが安価になります。
+/// インデックスは同じサイズの`XDBuf`と同じで、メモリ上の配置のみが異なります。
+#[derive(Debug, Clone)]
+pub struct TiledXDBuf<T, const D: usize, const TILE: usize> {
+    buf: Vec<T>,
+    size: [usize; D],
+    stride: [usize; D],
+    tile_stride: [usize; D],
+    tile_len: usize,
+}
+
+impl<T, const D: usize, const TILE: usize> TiledXDBuf<T, D, TILE> {
+    /// Computes the number of elements in a single tile.
+    ///
+    /// 1つのタイルの要素数を計算します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `TILE` is 0.
+    /// * Error if `TILE^D` exceeds the range of `usize`.
+    ///
+    /// * `TILE`が0の場合エラーになります。
+    /// * `TILE^D`が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// assert_eq!(TiledXDBuf::<i32, 3, 4>::calc_tile_len().unwrap(), 64);
+    /// ```
+    pub fn calc_tile_len() -> Result<usize, anyhow::Error> {
+        if TILE == 0 {
+            return Err(anyhow!("tile size is out of range"));
+        }
+
+        (0..D).try_fold(1_usize, |acc, _| {
+            acc.checked_mul(TILE).ok_or(
+                anyhow!("tile size is out of range")
+            )
+        })
+    }
+
+    /// Returns the number of tiles in each dimension needed to cover `size`.
+    ///
+    /// `size`を覆うのに必要な各次元のタイル数を返します。
+    fn calc_tiles(size: &[usize; D]) -> [usize; D] {
+        size.map(|s| s.div_ceil(TILE))
+    }
+
+    /// Generate a new `TiledXDBuf`.
+    ///
+    /// Allocates the specified amount of elements for each dimension, rounded up to whole tiles, and fills them with initial values.
+    ///
+    /// 新しい`TiledXDBuf`を生成します。
+    ///
+    /// それぞれの次元について指定した分の要素をタイル単位に切り上げて確保し、初期値で埋めます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    /// * Error if `TILE` is 0 or `TILE^D` exceeds the range of `usize`.
+    ///
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    /// * `TILE`が0、または`TILE^D`が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// let buf = TiledXDBuf::<i32, 3, 4>::new([3, 4, 5], 0).unwrap();
+    /// assert_eq!(buf.len(), 60);
+    /// ```
+    pub fn new(size: [usize; D], initial_value: T) -> Result<Self, anyhow::Error>
+    where
+        T: Clone,
+    {
+        let mut buf = Self {
+            buf: Vec::new(),
+            size,
+            stride: [0; D],
+            tile_stride: [0; D],
+            tile_len: 0,
+        };
+        buf.init(size, initial_value)?;
+
+        Ok(buf)
+    }
+
+    /// Initialize buffers.
+    ///
+    /// The internally allocated capacity is not affected in the shrink direction.
+    ///
+    /// バッファを初期化します。
+    ///
+    /// 内部に割り当てられた容量には縮小方向への影響を与えません。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    /// * Error if `TILE` is 0 or `TILE^D` exceeds the range of `usize`.
+    ///
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    /// * `TILE`が0、または`TILE^D`が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// let mut buf = TiledXDBuf::<i32, 3, 4>::new([3, 4, 5], 0).unwrap();
+    ///
+    /// buf.init([1, 2, 3], 1).unwrap();
+    /// assert_eq!(buf.len(), 6);
+    /// assert_eq!(buf.get(0), Some(&1));
+    /// ```
+    pub fn init(&mut self, size: [usize; D], initial_value: T) -> Result<(), anyhow::Error>
+    where
+        T: Clone,
+    {
+        XDBuf::<T, D>::calc_total_size(&size)?;
+        let tile_len = Self::calc_tile_len()?;
+        let tiles = Self::calc_tiles(&size);
+        let total_tiles = XDBuf::<T, D>::calc_total_size(&tiles)?;
+        let storage_len = total_tiles.checked_mul(tile_len).ok_or(
+            anyhow!("size is out of range")
+        )?;
+
+        self.size = size;
+        self.stride = XDBuf::<T, D>::calc_dim_stride(&size)?;
+        self.tile_stride = XDBuf::<T, D>::calc_dim_stride(&tiles)?;
+        self.tile_len = tile_len;
+
+        self.buf.clear();
+        self.buf.resize(storage_len, initial_value);
+
+        Ok(())
+    }
+
+    /// Generate a `TiledXDBuf` with the same size and contents as `buf`.
+    ///
+    /// `buf`と同じサイズと内容を持つ`TiledXDBuf`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `TILE` is 0 or `TILE^D` exceeds the range of `usize`.
+    /// * Error if the size rounded up to whole tiles exceeds the range of `usize`.
+    ///
+    /// * `TILE`が0、または`TILE^D`が`usize`の範囲を超える場合エラーになります。
+    /// * タイル単位に切り上げたサイズが`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{TiledXDBuf, XDBuf};
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([3, 3], (1..=9).collect()).unwrap();
+    /// let tiled = TiledXDBuf::<i32, 2, 2>::from_xdbuf(&buf).unwrap();
+    ///
+    /// assert_eq!(tiled.get_m([2, 1]), Some(&6));
+    /// ```
+    pub fn from_xdbuf<S: Storage<T>>(buf: &XDBuf<T, D, S>) -> Result<Self, anyhow::Error>
+    where
+        T: Clone,
+    {
+        // タイルの端の余白は参照されないので、任意の要素で埋めておけばよい
+        let mut tiled = Self::new(buf.size(), buf.as_slice()[0].clone())?;
+
+        for (index, value) in buf.indexed_iter() {
+            let storage_index = tiled.storage_index(&index);
+            tiled.buf[storage_index] = value.clone();
+        }
+
+        Ok(tiled)
+    }
+
+    /// Generate an `XDBuf` with the same size and contents as this buffer.
+    ///
+    /// このバッファと同じサイズと内容を持つ`XDBuf`を生成します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{TiledXDBuf, XDBuf};
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([3, 3], (1..=9).collect()).unwrap();
+    /// let tiled = TiledXDBuf::<i32, 2, 2>::from_xdbuf(&buf).unwrap();
+    ///
+    /// assert_eq!(tiled.to_xdbuf().iter().collect::<Vec<_>>(), buf.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn to_xdbuf(&self) -> XDBuf<T, D>
+    where
+        T: Clone,
+    {
+        let buf = self.idx_range()
+            .map(|i| self.buf[self.storage_index(&self.to_mul_dim_index(i))].clone())
+            .collect();
+
+        XDBuf {
+            buf,
+            size: self.size,
+            stride: self.stride,
+            layout: Layout::FirstMajor,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Convert an index in array notation to the position in the internal storage.
+    ///
+    /// 配列表記のインデックスを内部の格納位置に変換します。
+    fn storage_index(&self, index: &[usize; D]) -> usize {
+        let mut tile = 0;
+        let mut offset = 0;
+        let mut offset_stride = 1;
+
+        for (&i, tile_stride) in index.iter().zip(self.tile_stride) {
+            tile += i / TILE * tile_stride;
+            offset += i % TILE * offset_stride;
+            offset_stride *= TILE;
+        }
+
+        tile * self.tile_len + offset
+    }
+
+    /// Convert an index in array notation to a scalar index.
+    ///
+    /// Scalar indices are the same as for an `XDBuf` of the same size.
+    ///
+    /// 配列表記のインデックスをスカラーのインデックスに変換します。
+    ///
+    /// スカラーのインデックスは同じサイズの`XDBuf`と同じです。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// let buf = TiledXDBuf::<i32, 3, 2>::new([3, 4, 5], 0).unwrap();
+    /// assert_eq!(buf.to_scalar_index(&[1, 2, 3]).unwrap(), 3*4*3 + 3*2 + 1);
+    /// ```
+    pub fn to_scalar_index(&self, index: &[usize; D]) -> Result<usize, anyhow::Error> {
+        self.validate_index(index)?;
+
+        Ok(index.iter().zip(self.stride).map(|(&i, s)| i * s).sum())
+    }
+
+    /// Convert scalar index to array notation.
+    ///
+    /// スカラーのインデックスを配列表記に変換します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// let buf = TiledXDBuf::<i32, 3, 2>::new([3, 4, 5], 0).unwrap();
+    /// assert_eq!(buf.to_mul_dim_index(1 + 2*3 + 3*4*3), [1, 2, 3]);
+    /// ```
+    pub fn to_mul_dim_index(&self, mut scalar: usize) -> [usize; D] {
+        let mut index = [0; D];
+
+        for i in (0..D).rev() {
+            index[i] = scalar / self.stride[i];
+            scalar %= self.stride[i];
+        }
+
+        index
+    }
+
+    /// Checks for index integrity.
+    ///
+    /// インデックスの整合性をチェックします。
+    fn validate_index(&self, index: &[usize; D]) -> Result<(), anyhow::Error> {
+        let in_range = index.iter().zip(self.size.iter()).all(|(&i, &s)| i < s);
+
+        if in_range {
+            Ok(())
+        } else {
+            Err(anyhow!("index is out of range"))
+        }
+    }
+
+    /// Get a reference to the element specified by the scalar `index`.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// スカラーの`index`で指定された要素の参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+
+        self.buf.get(self.storage_index(&self.to_mul_dim_index(index)))
+    }
+
+    /// Get a variable reference to the element specified by the scalar `index`.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// スカラーの`index`で指定された要素の可変参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let storage_index = self.storage_index(&self.to_mul_dim_index(index));
+        self.buf.get_mut(storage_index)
+    }
+
+    /// Set `value` to the element specified by the scalar `index`.
+    ///
+    /// スカラーの`index`で指定された要素に`value`を設定します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    pub fn set(&mut self, index: usize, value: T) -> Result<(), anyhow::Error> {
+        *self.get_mut(index).ok_or(anyhow!("index is out of range"))? = value;
+
+        Ok(())
+    }
+
+    /// Get a reference to the element specified by `index` in array notation.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// 配列表記の`index`で指定された要素の参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// let mut buf = TiledXDBuf::<i32, 3, 2>::new([3, 4, 5], 0).unwrap();
+    /// buf.set_m([2, 3, 4], 100).unwrap();
+    ///
+    /// assert_eq!(buf.get_m([2, 3, 4]), Some(&100));
+    /// assert_eq!(buf.get_m([3, 0, 0]), None);
+    /// ```
+    pub fn get_m(&self, index: [usize; D]) -> Option<&T> {
+        self.validate_index(&index).ok()?;
+
+        self.buf.get(self.storage_index(&index))
+    }
+
+    /// Get a variable reference to the element specified by `index` in array notation.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// 配列表記の`index`で指定された要素の可変参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    pub fn get_mut_m(&mut self, index: [usize; D]) -> Option<&mut T> {
+        self.validate_index(&index).ok()?;
+
+        let storage_index = self.storage_index(&index);
+        self.buf.get_mut(storage_index)
+    }
+
+    /// Set `value` to the element specified by `index` in array notation.
+    ///
+    /// 配列表記の`index`で指定された要素に`value`を設定します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    pub fn set_m(&mut self, index: [usize; D], value: T) -> Result<(), anyhow::Error> {
+        *self.get_mut_m(index).ok_or(anyhow!("index is out of range"))? = value;
+
+        Ok(())
+    }
+
+    /// Returns an iterator over the elements of the buffer.
+    ///
+    /// Elements are visited tile by tile in storage order, and the unused part of the tiles on the edges is skipped.
+    ///
+    /// バッファの要素を走査するイテレータを返します。
+    ///
+    /// 要素は格納順にタイルごとに走査され、端のタイルの未使用部分は飛ばされます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// let buf = TiledXDBuf::<i32, 2, 2>::new([3, 3], 1).unwrap();
+    /// assert_eq!(buf.iter().count(), 9);
+    /// assert_eq!(buf.iter().sum::<i32>(), 9);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.indexed_iter().map(|(_, v)| v)
+    }
+
+    /// Returns an iterator that allows modifying each element of the buffer.
+    ///
+    /// Elements are visited tile by tile in storage order.
+    ///
+    /// バッファの要素を変更しながら走査するイテレータを返します。
+    ///
+    /// 要素は格納順にタイルごとに走査されます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// let mut buf = TiledXDBuf::<i32, 2, 2>::new([3, 3], 1).unwrap();
+    /// buf.iter_mut().for_each(|v| *v *= 2);
+    /// assert_eq!(buf.get_m([2, 2]), Some(&2));
+    /// ```
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.indexed_iter_mut().map(|(_, v)| v)
+    }
+
+    /// Returns an iterator over the elements of the buffer together with their indices in array notation.
+    ///
+    /// Elements are visited tile by tile in storage order, which is the cache-friendly order for this layout.
+    ///
+    /// バッファの要素を配列表記のインデックスと共に走査するイテレータを返します。
+    ///
+    /// 要素は格納順にタイルごとに走査され、この配置においてキャッシュ効率の良い順序になります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::TiledXDBuf;
+    ///
+    /// let buf = TiledXDBuf::<i32, 2, 2>::new([3, 3], 1).unwrap();
+    /// assert_eq!(buf.indexed_iter().count(), 9);
+    /// assert_eq!(buf.indexed_iter().nth(2), Some(([0, 1], &1)));
+    /// ```
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ([usize; D], &T)> + '_ {
+        self.buf.iter().enumerate().filter_map(move |(i, v)| {
+            let index = self.storage_to_mul_dim_index(i);
+            self.validate_index(&index).ok().map(|_| (index, v))
+        })
+    }
+
+    /// Returns an iterator that allows modifying each element of the buffer together with its index in array notation.
+    ///
+    /// Elements are visited tile by tile in storage order.
+    ///
+    /// バッファの要素を配列表記のインデックスと共に変更しながら走査するイテレータを返します。
+    ///
+    /// 要素は格納順にタイルごとに走査されます。
+    pub fn indexed_iter_mut(&mut self) -> impl Iterator<Item = ([usize; D], &mut T)> + '_ {
+        let size = self.size;
+        let tile_stride = self.tile_stride;
+        let tile_len = self.tile_len;
+
+        self.buf.iter_mut().enumerate().filter_map(move |(i, v)| {
+            let index = storage_to_mul_dim_index::<D, TILE>(i, &tile_stride, tile_len);
+            index.iter().zip(size).all(|(&i, s)| i < s).then_some((index, v))
+        })
+    }
+
+    /// Convert the position in the internal storage to an index in array notation.
+    ///
+    /// 内部の格納位置を配列表記のインデックスに変換します。
+    fn storage_to_mul_dim_index(&self, storage_index: usize) -> [usize; D] {
+        storage_to_mul_dim_index::<D, TILE>(storage_index, &self.tile_stride, self.tile_len)
+    }
+
+    /// Generates a `Walker` with the specified `index` as its initial position.
+    ///
+    /// 指定された`index`を初期位置として`Walker`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{step3d, TiledXDBuf};
+    ///
+    /// let mut buf = TiledXDBuf::<i32, 3, 2>::new([3, 4, 5], 0).unwrap();
+    /// buf.set_m([1, 1, 2], 1).unwrap();
+    ///
+    /// let mut walker = buf.walker_from_m([1, 1, 1]).unwrap();
+    /// walker.as_(&step3d::TOP).unwrap();
+    ///
+    /// assert_eq!(buf.get(walker.index_s()), Some(&1));
+    /// ```
+    pub fn walker_from_m(&self, index: [usize; D]) -> Result<Walker<'_, T, D, Self>, anyhow::Error> {
+        let scalar = self.to_scalar_index(&index)?;
+
+        self.walker_from_s(scalar)
+    }
+
+    /// Generates a `Walker` with the specified `scalar_index` as its initial position.
+    ///
+    /// 指定された`scalar_index`を初期位置として`Walker`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `scalar_index` is out of range.
+    ///
+    /// * `scalar_index`が範囲外の場合エラーになります。
+    pub fn walker_from_s(&self, scalar_index: usize) -> Result<Walker<'_, T, D, Self>, anyhow::Error> {
+        if scalar_index >= self.len() {
+            return Err(anyhow!("index is out of range"));
+        }
+
+        Ok(Walker::new(self, scalar_index))
+    }
+
+    /// Returns the number of elements in the buffer.
+    ///
+    /// Padding at the edges of the tiles is not included.
+    ///
+    /// バッファの要素数を返します。
+    ///
+    /// タイル端の余白は含まれません。
+    pub fn len(&self) -> usize {
+        self.size.iter().product()
+    }
+
+    /// Returns `true` if the buffer contains no elements.
+    ///
+    /// バッファが要素を持たない場合に`true`を返します。
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a range of buffer indices.
+    ///
+    /// バッファのインデックスの範囲を返します。
+    pub fn idx_range(&self) -> Range<usize> {
+        0..self.len()
+    }
+
+    /// Returns the size of each dimension of the buffer.
+    ///
+    /// バッファの各次元のサイズを返します。
+    pub fn size(&self) -> [usize; D] {
+        self.size
+    }
+
+    /// Reduce buffer capacity as much as possible.
+    ///
+    /// バッファが確保しているメモリ容量をできるだけ縮小します。
+    pub fn shrink_to_fit(&mut self) {
+        self.buf.shrink_to_fit();
+    }
+}
+
+/// Convert the position in the storage of a `TiledXDBuf` to an index in array notation.
+///
+/// `TiledXDBuf`の格納位置を配列表記のインデックスに変換します。
+fn storage_to_mul_dim_index<const D: usize, const TILE: usize>(
+    storage_index: usize,
+    tile_stride: &[usize; D],
+    tile_len: usize,
+) -> [usize; D] {
+    let mut tile = storage_index / tile_len;
+    let mut offset = storage_index % tile_len;
+    let mut index = [0; D];
+
+    for i in (0..D).rev() {
+        index[i] = tile / tile_stride[i] * TILE;
+        tile %= tile_stride[i];
+    }
+
+    for i in index.iter_mut() {
+        *i += offset % TILE;
+        offset /= TILE;
+    }
+
+    index
+}
+
+impl<T, const D: usize, const TILE: usize> Walkable<T, D> for TiledXDBuf<T, D, TILE> {
+    fn size(&self) -> [usize; D] {
+        self.size
+    }
+
+    fn len(&self) -> usize {
+        TiledXDBuf::len(self)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        TiledXDBuf::get(self, index)
+    }
+
+    fn to_scalar_index(&self, index: &[usize; D]) -> Result<usize, anyhow::Error> {
+        TiledXDBuf::to_scalar_index(self, index)
+    }
+
+    fn to_mul_dim_index(&self, scalar: usize) -> [usize; D] {
+        TiledXDBuf::to_mul_dim_index(self, scalar)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_xdbuf() {
+        let buf = XDBuf::<usize, 3>::new_with_vec([5, 3, 7], (0..105).collect()).unwrap();
+        let tiled = TiledXDBuf::<usize, 3, 4>::from_xdbuf(&buf).unwrap();
+
+        for (index, value) in buf.indexed_iter() {
+            assert_eq!(tiled.get_m(index), Some(value));
+        }
+
+        assert_eq!(tiled.to_xdbuf().buf, buf.buf);
+    }
+
+    #[test]
+    fn indexed_iter_mut_visits_every_element_once() {
+        let mut tiled = TiledXDBuf::<usize, 2, 3>::new([4, 5], 0).unwrap();
+        tiled.indexed_iter_mut().for_each(|(index, v)| *v += index[0] + index[1] * 4 + 1);
+
+        let buf = tiled.to_xdbuf();
+        assert_eq!(buf.buf, (1..=20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn iter_skips_padding_of_edge_tiles() {
+        let mut tiled = TiledXDBuf::<usize, 3, 4>::new([5, 3, 2], 1).unwrap();
+        assert_eq!(tiled.iter().count(), 30);
+
+        tiled.iter_mut().for_each(|v| *v += 1);
+        assert_eq!(tiled.iter().sum::<usize>(), 60);
+        assert_eq!(tiled.to_xdbuf().buf, vec![2; 30]);
+    }
+
+    #[test]
+    fn walker_moves_across_tiles() {
+        let buf = XDBuf::<usize, 2>::new_with_vec([5, 5], (0..25).collect()).unwrap();
+        let tiled = TiledXDBuf::<usize, 2, 2>::from_xdbuf(&buf).unwrap();
+
+        let mut walker = tiled.walker_from_m([1, 1]).unwrap();
+        walker.as_(&[1, 1]).unwrap();
+        assert_eq!(walker.index_m(), [2, 2]);
+        assert_eq!(tiled.get(walker.index_s()), Some(&12));
+
+        walker.as_until(|&v, _| v == 20).unwrap();
+        assert_eq!(walker.index_m(), [0, 4]);
+        assert!(walker.index_(&[0, 1]).is_err());
+    }
+
+    #[test]
+    fn rejects_zero_tile() {
+        assert!(TiledXDBuf::<usize, 2, 0>::new([2, 2], 0).is_err());
+    }
+}
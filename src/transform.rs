@@ -0,0 +1,509 @@
+use std::marker::PhantomData;
+
+use anyhow::anyhow;
+
+use crate::storage::{Storage, StorageMut};
+use crate::xdbuf::to_mul_dim_index;
+use crate::XDBuf;
+
+/// Symmetry of a `D`-dimensional box made of axis permutations and reflections
+///
+/// Axis `a` of the transformed buffer corresponds to an axis of the original buffer, possibly reversed.
+/// There are 8 symmetries in 2D and 48 in 3D. The same symmetry can be applied to buffers, indices and
+/// steps such as the constants of `step2d` and `step3d`, so that walkers can be transformed consistently with the buffer.
+///
+/// 軸の置換と反転からなる`D`次元の直方体の対称変換です。
+///
+/// 変換後のバッファの軸`a`は、反転している可能性のある元のバッファの軸に対応します。
+/// 2次元では8個、3次元では48個の対称変換があります。同じ対称変換をバッファ、インデックス、
+/// `step2d`や`step3d`の定数などのステップに適用できるため、ウォーカーをバッファと一貫して変換できます。
+///
+/// # Example
+///
+/// ```
+/// use xdbuf::{step2d, Symmetry};
+///
+/// let rotation = Symmetry::<2>::rotate90(1, (0, 1)).unwrap();
+///
+/// assert_eq!(rotation.transform_step(step2d::RIGHT), step2d::UP);
+/// assert_eq!(rotation.transform_step(step2d::UP), step2d::LEFT);
+/// assert_eq!(rotation.then(&rotation.inverse()), Symmetry::identity());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symmetry<const D: usize> {
+    axes: [usize; D],
+    reversed: [bool; D],
+}
+
+impl<const D: usize> Symmetry<D> {
+    /// Generate the symmetry that changes nothing.
+    ///
+    /// 何も変更しない対称変換を生成します。
+    pub fn identity() -> Self {
+        Self {
+            axes: std::array::from_fn(|a| a),
+            reversed: [false; D],
+        }
+    }
+
+    /// Generate the rotation by `k` quarter turns in `plane`, turning the first axis of the plane towards the second one.
+    ///
+    /// Negative `k` rotates in the opposite direction.
+    ///
+    /// `plane`内で`k`回の4分の1回転を行う、平面の最初の軸を2番目の軸に向ける回転を生成します。
+    ///
+    /// 負の`k`は逆方向に回転します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if an axis of `plane` is out of range.
+    /// * Error if the two axes of `plane` are the same.
+    ///
+    /// * `plane`の軸が範囲外の場合エラーになります。
+    /// * `plane`の2つの軸が同じ場合エラーになります。
+    pub fn rotate90(k: isize, plane: (usize, usize)) -> Result<Self, anyhow::Error> {
+        let (a, b) = plane;
+        if a >= D || b >= D {
+            return Err(anyhow!("axis is out of range"));
+        }
+        if a == b {
+            return Err(anyhow!("axes of plane must differ"));
+        }
+
+        let mut symmetry = Self::identity();
+        match k.rem_euclid(4) {
+            1 => {
+                (symmetry.axes[a], symmetry.axes[b]) = (b, a);
+                symmetry.reversed[a] = true;
+            }
+            2 => {
+                symmetry.reversed[a] = true;
+                symmetry.reversed[b] = true;
+            }
+            3 => {
+                (symmetry.axes[a], symmetry.axes[b]) = (b, a);
+                symmetry.reversed[b] = true;
+            }
+            _ => {}
+        }
+
+        Ok(symmetry)
+    }
+
+    /// Generate the reflection that reverses `axis`.
+    ///
+    /// `axis`を反転する鏡映を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    pub fn flip(axis: usize) -> Result<Self, anyhow::Error> {
+        if axis >= D {
+            return Err(anyhow!("axis is out of range"));
+        }
+
+        let mut symmetry = Self::identity();
+        symmetry.reversed[axis] = true;
+
+        Ok(symmetry)
+    }
+
+    /// Returns all the symmetries of a `D`-dimensional box, starting with the identity.
+    ///
+    /// `D`次元の直方体のすべての対称変換を、恒等変換から順に返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::Symmetry;
+    ///
+    /// assert_eq!(Symmetry::<2>::all().count(), 8);
+    /// assert_eq!(Symmetry::<3>::all().count(), 48);
+    /// ```
+    pub fn all() -> impl Iterator<Item = Self> {
+        let mut permutations = vec![std::array::from_fn::<usize, D, _>(|a| a)];
+
+        // 辞書順で次の置換を順に生成する
+        loop {
+            let mut axes = *permutations.last().unwrap_or(&[0; D]);
+            let Some(i) = (1..D).rev().find(|&i| axes[i - 1] < axes[i]) else {
+                break;
+            };
+            let j = (i..D).rev().find(|&j| axes[i - 1] < axes[j]).unwrap_or(i);
+
+            axes.swap(i - 1, j);
+            axes[i..].reverse();
+            permutations.push(axes);
+        }
+
+        permutations.into_iter().flat_map(|axes| {
+            (0..1_usize << D).map(move |bits| Self {
+                axes,
+                reversed: std::array::from_fn(|a| bits >> a & 1 == 1),
+            })
+        })
+    }
+
+    /// Returns the symmetry that applies this symmetry and then `other`.
+    ///
+    /// この対称変換を適用した後に`other`を適用する対称変換を返します。
+    pub fn then(&self, other: &Self) -> Self {
+        Self {
+            axes: other.axes.map(|a| self.axes[a]),
+            reversed: std::array::from_fn(|a| other.reversed[a] ^ self.reversed[other.axes[a]]),
+        }
+    }
+
+    /// Returns the symmetry that undoes this symmetry.
+    ///
+    /// この対称変換を元に戻す対称変換を返します。
+    pub fn inverse(&self) -> Self {
+        let mut inverse = Self::identity();
+
+        for a in 0..D {
+            inverse.axes[self.axes[a]] = a;
+            inverse.reversed[self.axes[a]] = self.reversed[a];
+        }
+
+        inverse
+    }
+
+    /// Returns the size of a buffer of `size` after the transformation.
+    ///
+    /// `size`のバッファの変換後のサイズを返します。
+    pub fn transform_size(&self, size: [usize; D]) -> [usize; D] {
+        self.axes.map(|a| size[a])
+    }
+
+    /// Returns the index in array notation that `index` in a buffer of `size` moves to.
+    ///
+    /// `size`のバッファの`index`が移動する先の配列表記のインデックスを返します。
+    pub fn transform_index(&self, index: [usize; D], size: [usize; D]) -> [usize; D] {
+        std::array::from_fn(|a| {
+            let i = index[self.axes[a]];
+            if self.reversed[a] { size[self.axes[a]] - 1 - i } else { i }
+        })
+    }
+
+    /// Returns the step that `step` becomes after the transformation.
+    ///
+    /// `step`の変換後のステップを返します。
+    pub fn transform_step(&self, step: [isize; D]) -> [isize; D] {
+        std::array::from_fn(|a| {
+            let s = step[self.axes[a]];
+            if self.reversed[a] { -s } else { s }
+        })
+    }
+}
+
+impl<T: Clone, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
+    /// Generate a copy of the buffer transformed by `symmetry`.
+    ///
+    /// The result has the memory layout of this buffer.
+    ///
+    /// `symmetry`で変換したバッファの複製を生成します。
+    ///
+    /// 結果はこのバッファのメモリ配置を持ちます。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Symmetry, XDBuf};
+    ///
+    /// let buf = XDBuf::<u8, 2>::new_with_vec([3, 2], vec![
+    ///     1, 2, 3,
+    ///     4, 5, 6,
+    /// ]).unwrap();
+    ///
+    /// // 転置
+    /// let transposed = buf.transform(&Symmetry::rotate90(1, (0, 1)).unwrap().then(&Symmetry::flip(0).unwrap()));
+    ///
+    /// assert_eq!(transposed.size(), [2, 3]);
+    /// assert_eq!(transposed.as_slice(), &[
+    ///     1, 4,
+    ///     2, 5,
+    ///     3, 6,
+    /// ]);
+    /// ```
+    pub fn transform(&self, symmetry: &Symmetry<D>) -> XDBuf<T, D> {
+        let size = symmetry.transform_size(self.size);
+        // 要素数は変わらない
+        let stride = XDBuf::<T, D>::calc_dim_stride_with_layout(&size, self.layout).unwrap();
+        let inverse = symmetry.inverse();
+
+        let buf = (0..self.len()).map(|i| {
+            let source = inverse.transform_index(to_mul_dim_index(i, &stride, self.layout), size);
+            self.as_slice()[source.iter().zip(self.stride).map(|(&i, s)| i * s).sum::<usize>()].clone()
+        }).collect();
+
+        XDBuf {
+            buf,
+            size,
+            stride,
+            layout: self.layout,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Generate a copy of the buffer rotated by `k` quarter turns in `plane`.
+    ///
+    /// `plane`内で`k`回4分の1回転したバッファの複製を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if an axis of `plane` is out of range.
+    /// * Error if the two axes of `plane` are the same.
+    ///
+    /// * `plane`の軸が範囲外の場合エラーになります。
+    /// * `plane`の2つの軸が同じ場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// // yは上向き
+    /// let buf = XDBuf::<u8, 2>::new_with_vec([3, 2], vec![
+    ///     1, 2, 3,
+    ///     4, 5, 6,
+    /// ]).unwrap();
+    ///
+    /// let rotated = buf.rotate90(1, (0, 1)).unwrap();
+    ///
+    /// assert_eq!(rotated.size(), [2, 3]);
+    /// assert_eq!(rotated.as_slice(), &[
+    ///     4, 1,
+    ///     5, 2,
+    ///     6, 3,
+    /// ]);
+    /// ```
+    pub fn rotate90(&self, k: isize, plane: (usize, usize)) -> Result<XDBuf<T, D>, anyhow::Error> {
+        Ok(self.transform(&Symmetry::rotate90(k, plane)?))
+    }
+
+    /// Generate a copy of the buffer reversed along `axis`.
+    ///
+    /// `axis`に沿って反転したバッファの複製を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<u8, 2>::new_with_vec([3, 2], vec![1, 2, 3, 4, 5, 6]).unwrap();
+    ///
+    /// assert_eq!(buf.flip(0).unwrap().as_slice(), &[3, 2, 1, 6, 5, 4]);
+    /// assert_eq!(buf.flip(1).unwrap().as_slice(), &[4, 5, 6, 1, 2, 3]);
+    /// ```
+    pub fn flip(&self, axis: usize) -> Result<XDBuf<T, D>, anyhow::Error> {
+        Ok(self.transform(&Symmetry::flip(axis)?))
+    }
+
+    /// Returns an iterator over all the symmetries of the box together with the buffer transformed by each of them.
+    ///
+    /// In 2D, these are the 8 rotations and reflections of a tile.
+    ///
+    /// 直方体のすべての対称変換と、それぞれで変換したバッファを走査するイテレータを返します。
+    ///
+    /// 2次元では、タイルの8通りの回転と鏡映になります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let tile = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![1, 0, 0, 0]).unwrap();
+    ///
+    /// let variants = tile.symmetries().map(|(_, buf)| buf.as_slice().to_vec()).collect::<Vec<_>>();
+    /// assert_eq!(variants.len(), 8);
+    ///
+    /// // 角の位置は4通り
+    /// assert_eq!(variants.iter().filter(|v| v[0] == 1).count(), 2);
+    /// ```
+    pub fn symmetries(&self) -> impl Iterator<Item = (Symmetry<D>, XDBuf<T, D>)> + '_ {
+        Symmetry::all().map(|symmetry| (symmetry, self.transform(&symmetry)))
+    }
+}
+
+impl<T, const D: usize, S: StorageMut<T>> XDBuf<T, D, S> {
+    /// Transforms the buffer by `symmetry` in place.
+    ///
+    /// `symmetry`でバッファをその場で変換します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `symmetry` changes the size of the buffer.
+    ///
+    /// * `symmetry`がバッファのサイズを変える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Symmetry, XDBuf};
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    ///
+    /// buf.transform_inplace(&Symmetry::rotate90(2, (0, 1)).unwrap()).unwrap();
+    /// assert_eq!(buf.as_slice(), &[4, 3, 2, 1]);
+    ///
+    /// let mut wide = XDBuf::<u8, 2>::new([3, 2], 0).unwrap();
+    /// assert!(wide.transform_inplace(&Symmetry::rotate90(1, (0, 1)).unwrap()).is_err());
+    /// ```
+    pub fn transform_inplace(&mut self, symmetry: &Symmetry<D>) -> Result<(), anyhow::Error> {
+        if symmetry.transform_size(self.size) != self.size {
+            return Err(anyhow!("transform must keep the size"));
+        }
+
+        let (size, stride, layout) = (self.size, self.stride, self.layout);
+        let inverse = symmetry.inverse();
+        let source = |i: usize| -> usize {
+            let index = inverse.transform_index(to_mul_dim_index(i, &stride, layout), size);
+            index.iter().zip(stride).map(|(&i, s)| i * s).sum()
+        };
+
+        // 置換の巡回ごとに、移動元の要素と順に入れ替える
+        let slice = self.as_mut_slice();
+        let mut visited = vec![false; slice.len()];
+        for start in 0..slice.len() {
+            let mut current = start;
+            while !visited[current] {
+                visited[current] = true;
+
+                let next = source(current);
+                if next != start {
+                    slice.swap(current, next);
+                }
+                current = next;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rotates the buffer by `k` quarter turns in `plane` in place.
+    ///
+    /// `plane`内で`k`回4分の1回転するようにバッファをその場で回転します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if an axis of `plane` is out of range.
+    /// * Error if the two axes of `plane` are the same.
+    /// * Error if the rotation changes the size, i.e. the buffer is not square in `plane` and `k` is odd.
+    ///
+    /// * `plane`の軸が範囲外の場合エラーになります。
+    /// * `plane`の2つの軸が同じ場合エラーになります。
+    /// * 回転がサイズを変える場合、つまりバッファが`plane`内で正方形でなく`k`が奇数の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// buf.rotate90_inplace(-1, (0, 1)).unwrap();
+    ///
+    /// assert_eq!(buf.as_slice(), &[2, 4, 1, 3]);
+    /// ```
+    pub fn rotate90_inplace(&mut self, k: isize, plane: (usize, usize)) -> Result<(), anyhow::Error> {
+        self.transform_inplace(&Symmetry::rotate90(k, plane)?)
+    }
+
+    /// Reverses the buffer along `axis` in place.
+    ///
+    /// `axis`に沿ってバッファをその場で反転します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `axis` is out of range.
+    ///
+    /// * `axis`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<u8, 2>::new_with_vec([3, 2], vec![1, 2, 3, 4, 5, 6]).unwrap();
+    /// buf.flip_inplace(0).unwrap();
+    ///
+    /// assert_eq!(buf.as_slice(), &[3, 2, 1, 6, 5, 4]);
+    /// ```
+    pub fn flip_inplace(&mut self, axis: usize) -> Result<(), anyhow::Error> {
+        self.transform_inplace(&Symmetry::flip(axis)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{step3d, Layout};
+
+    use super::*;
+
+    #[test]
+    fn symmetries_form_a_group_consistent_with_steps() {
+        let all = Symmetry::<3>::all().collect::<Vec<_>>();
+        let size = [2, 3, 4];
+
+        for a in &all {
+            assert_eq!(a.then(&a.inverse()), Symmetry::identity());
+
+            for b in &all {
+                let ab = a.then(b);
+                assert!(all.contains(&ab));
+
+                let index = [1, 0, 3];
+                let moved = b.transform_index(a.transform_index(index, size), a.transform_size(size));
+                assert_eq!(ab.transform_index(index, size), moved);
+                assert_eq!(ab.transform_step(step3d::RIGHT_BACK_TOP), b.transform_step(a.transform_step(step3d::RIGHT_BACK_TOP)));
+            }
+        }
+    }
+
+    #[test]
+    fn transform_moves_neighbours_along_transformed_steps() {
+        let buf = XDBuf::<usize, 3>::new_with_layout([2, 3, 4], 0, Layout::LastMajor).unwrap().map_indexed(|i, _| i[0] + 10 * i[1] + 100 * i[2]);
+
+        for symmetry in Symmetry::<3>::all() {
+            let transformed = buf.transform(&symmetry);
+
+            for (index, v) in buf.indexed_iter() {
+                let moved = symmetry.transform_index(index, buf.size());
+                assert_eq!(transformed.get_m(moved), Some(v));
+            }
+
+            let walker = buf.walker_from_m([0, 0, 0]).unwrap();
+            let start = symmetry.transform_index([0, 0, 0], buf.size());
+            let moved = transformed.walker_from_m(start).unwrap().index_(&symmetry.transform_step(step3d::FRONT)).unwrap();
+            assert_eq!(transformed.get(moved), buf.get(walker.index_(&step3d::FRONT).unwrap()));
+        }
+    }
+
+    #[test]
+    fn inplace_matches_copy() {
+        let buf = XDBuf::<u32, 3>::new([3, 3, 2], 0).unwrap().map_indexed(|i, _| (i[0] + 3 * i[1] + 9 * i[2]) as u32);
+
+        for k in -2..=5 {
+            let mut inplace = buf.clone();
+            inplace.rotate90_inplace(k, (0, 1)).unwrap();
+            assert_eq!(inplace.as_slice(), buf.rotate90(k, (0, 1)).unwrap().as_slice());
+        }
+
+        let mut flipped = buf.clone();
+        flipped.flip_inplace(2).unwrap();
+        assert_eq!(flipped.as_slice(), buf.flip(2).unwrap().as_slice());
+
+        let mut odd = buf.clone();
+        assert!(odd.rotate90_inplace(1, (1, 2)).is_err());
+        assert!(odd.rotate90_inplace(2, (1, 2)).is_ok());
+        assert!(odd.rotate90_inplace(1, (1, 1)).is_err());
+        assert!(odd.flip_inplace(3).is_err());
+    }
+}
@@ -1,15 +1,89 @@
+use std::fmt;
+use std::marker::PhantomData;
+
 use anyhow::anyhow;
 
 use crate::XDBuf;
 
+/// Trait for buffers that can be traversed by a `Walker`.
+///
+/// Scalar indices are the positions in the order given by `to_mul_dim_index`, regardless of how the elements are stored.
+///
+/// `Walker`で走査できるバッファを表すトレイトです。
+///
+/// スカラーのインデックスは、要素の格納方法によらず`to_mul_dim_index`で与えられる順序での位置を表します。
+pub trait Walkable<T, const D: usize> {
+    /// Returns the size of each dimension of the buffer.
+    ///
+    /// バッファの各次元のサイズを返します。
+    fn size(&self) -> [usize; D];
+
+    /// Returns the number of elements in the buffer.
+    ///
+    /// バッファの要素数を返します。
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the buffer contains no elements.
+    ///
+    /// バッファが要素を持たない場合に`true`を返します。
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a reference to the element specified by the scalar `index`.
+    ///
+    /// スカラーの`index`で指定された要素の参照を取得します。
+    fn get(&self, index: usize) -> Option<&T>;
+
+    /// Convert an index in array notation to a scalar index.
+    ///
+    /// 配列表記のインデックスをスカラーのインデックスに変換します。
+    fn to_scalar_index(&self, index: &[usize; D]) -> Result<usize, anyhow::Error>;
+
+    /// Convert scalar index to array notation.
+    ///
+    /// スカラーのインデックスを配列表記に変換します。
+    fn to_mul_dim_index(&self, scalar: usize) -> [usize; D];
+}
+
 /// `XDBuf`におけるインデックス操作を行うための構造体
-#[derive(Debug, Clone, Copy)]
-pub struct Walker<'a, T, const D: usize> {
-    pub(super) buf_into: &'a XDBuf<T, D>,
+///
+/// `B`には`Walkable`を実装した任意のバッファを指定できます。
+pub struct Walker<'a, T, const D: usize, B: ?Sized = XDBuf<T, D>> {
+    pub(super) buf_into: &'a B,
     pub(super) current_index: usize,
+    _marker: PhantomData<fn() -> T>,
 }
 
-impl<'a, T, const D: usize> Walker<'a, T, D> {
+impl<T, const D: usize, B: ?Sized> Clone for Walker<'_, T, D, B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, const D: usize, B: ?Sized> Copy for Walker<'_, T, D, B> {}
+
+impl<T, const D: usize, B: fmt::Debug + ?Sized> fmt::Debug for Walker<'_, T, D, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Walker")
+            .field("buf_into", &self.buf_into)
+            .field("current_index", &self.current_index)
+            .finish()
+    }
+}
+
+impl<'a, T, const D: usize, B: Walkable<T, D> + ?Sized> Walker<'a, T, D, B> {
+    /// Generates a `Walker` positioned at `current_index` of `buf_into`.
+    ///
+    /// `buf_into`の`current_index`に位置する`Walker`を生成します。
+    pub(crate) fn new(buf_into: &'a B, current_index: usize) -> Self {
+        Self {
+            buf_into,
+            current_index,
+            _marker: PhantomData,
+        }
+    }
+
     /// Returns the current index.
     ///
     /// 現在のインデックスを返します。
@@ -76,7 +150,7 @@ impl<'a, T, const D: usize> Walker<'a, T, D> {
                 Ok(())
             })?;
 
-        Ok(self.buf_into.to_scalar_index(&index)?)
+        self.buf_into.to_scalar_index(&index)
     }
 
     /// Moves to the current index plus `step`.
@@ -1,24 +1,33 @@
+use std::marker::PhantomData;
 use std::ops::Range;
 
 use anyhow::anyhow;
 
-use crate::walker::Walker;
+use crate::layout::Layout;
+use crate::storage::{Storage, StorageMut};
+use crate::walker::{Walkable, Walker};
 
 /// Structure representing an n-dimensional buffer
 ///
 /// Reusing a single instance reduces memory allocation.
 ///
+/// Elements are held in `S`. Reinitialization such as `init` is only available for the growable `Vec<T>`.
+///
 /// n次元のバッファを表す構造体です。
 ///
 /// 単一のインスタンスを再利用することで、メモリの割り当てを削減できます。
+///
+/// 要素は`S`に格納されます。`init`などの再初期化は伸長可能な`Vec<T>`の場合のみ利用できます。
 #[derive(Debug, Clone)]
-pub struct XDBuf<T, const D: usize> {
-    buf: Vec<T>,
-    size: [usize; D],
-    stride: [usize; D],
+pub struct XDBuf<T, const D: usize, S = Vec<T>> {
+    pub(crate) buf: S,
+    pub(crate) size: [usize; D],
+    pub(crate) stride: [usize; D],
+    pub(crate) layout: Layout,
+    pub(crate) _marker: PhantomData<fn() -> T>,
 }
 
-impl<T, const D: usize> XDBuf<T, D> {
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
     /// Convert an index in array notation to a scalar index.
     ///
     /// 配列表記のインデックスをスカラーのインデックスに変換します。
@@ -67,15 +76,8 @@ impl<T, const D: usize> XDBuf<T, D> {
     ///
     /// assert_eq!(index, [1, 2, 3]);
     /// ```
-    pub fn to_mul_dim_index(&self, mut scalar: usize) -> [usize; D] {
-        let mut index = [0; D];
-
-        for i in (0..D).rev() {
-            index[i] = scalar / self.stride[i];
-            scalar %= self.stride[i];
-        }
-
-        index
+    pub fn to_mul_dim_index(&self, scalar: usize) -> [usize; D] {
+        to_mul_dim_index(scalar, &self.stride, self.layout)
     }
 
     /// Checks for index integrity.
@@ -112,7 +114,7 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// assert_eq!(total_size, 60);
     /// ```
     pub fn calc_total_size(size: &[usize; D]) -> Result<usize, anyhow::Error> {
-        if size.iter().any(|&v| v == 0) {
+        if size.contains(&0) {
             return Err(anyhow!("size is out of range"));
         }
 
@@ -125,8 +127,12 @@ impl<T, const D: usize> XDBuf<T, D> {
 
     /// Calculates the number of elements each dimension of a multidimensional array has.
     ///
+    /// The stride is calculated for the default `Layout::FirstMajor`.
+    ///
     /// 多次元配列の各次元が持つ要素数を計算します。
     ///
+    /// ストライドは既定の`Layout::FirstMajor`について計算されます。
+    ///
     /// # Example
     ///
     /// ```
@@ -138,10 +144,36 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// assert_eq!(stride, [1, 3, 12]);
     /// ```
     pub fn calc_dim_stride(size: &[usize; D]) -> Result<[usize; D], anyhow::Error> {
+        Self::calc_dim_stride_with_layout(size, Layout::FirstMajor)
+    }
+
+    /// Calculates the number of elements each dimension of a multidimensional array has, for the given `layout`.
+    ///
+    /// 指定された`layout`について、多次元配列の各次元が持つ要素数を計算します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Layout, XDBuf};
+    ///
+    /// let size = [3, 4, 5];
+    ///
+    /// let stride = XDBuf::<i32, 3>::calc_dim_stride_with_layout(&size, Layout::FirstMajor).unwrap();
+    /// assert_eq!(stride, [1, 3, 12]);
+    ///
+    /// let stride = XDBuf::<i32, 3>::calc_dim_stride_with_layout(&size, Layout::LastMajor).unwrap();
+    /// assert_eq!(stride, [20, 5, 1]);
+    /// ```
+    pub fn calc_dim_stride_with_layout(size: &[usize; D], layout: Layout) -> Result<[usize; D], anyhow::Error> {
         let mut stride = [1_usize; D];
-        for i in 0..D {
-            for j in 0..i {
-                stride[i] = stride[i].checked_mul(size[j]).ok_or(
+        let mut acc = 1_usize;
+
+        for (n, i) in layout.axes_fast_to_slow::<D>().into_iter().enumerate() {
+            stride[i] = acc;
+
+            // 最も遅い軸のサイズはストライドに影響しない
+            if n + 1 < D {
+                acc = acc.checked_mul(size[i]).ok_or(
                     anyhow!("size is out of range")
                 )?;
             }
@@ -150,6 +182,79 @@ impl<T, const D: usize> XDBuf<T, D> {
         Ok(stride)
     }
 
+    /// Generate an `XDBuf` that uses `storage` as its elements.
+    ///
+    /// Any `Storage` such as `Box<[T]>`, `&[T]`, `&mut [T]` or `Arc<[T]>` can be used, so memory owned by others can be wrapped without copying.
+    ///
+    /// `storage`を要素として使用する`XDBuf`を生成します。
+    ///
+    /// `Box<[T]>`、`&[T]`、`&mut [T]`、`Arc<[T]>`などの任意の`Storage`を使用できるため、他が所有するメモリを複製せずに扱えます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `storage` does not match the total product of `size`.
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `storage`の長さが`size`の総積と一致しない場合エラーになります。
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut data = [0.0_f32; 6];
+    ///
+    /// let mut buf = XDBuf::<f32, 2, _>::from_storage([3, 2], &mut data[..]).unwrap();
+    /// buf.set_m([2, 1], 1.0).unwrap();
+    ///
+    /// assert_eq!(data[5], 1.0);
+    /// ```
+    pub fn from_storage(size: [usize; D], storage: S) -> Result<Self, anyhow::Error> {
+        Self::from_storage_with_layout(size, storage, Layout::default())
+    }
+
+    /// Generate an `XDBuf` with the specified memory `layout` that uses `storage` as its elements.
+    ///
+    /// 指定したメモリ配置`layout`で、`storage`を要素として使用する`XDBuf`を生成します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `storage` does not match the total product of `size`.
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `storage`の長さが`size`の総積と一致しない場合エラーになります。
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use xdbuf::{Layout, XDBuf};
+    ///
+    /// let data: Arc<[i32]> = Arc::from(vec![1, 2, 3, 4, 5, 6]);
+    /// let buf = XDBuf::<i32, 2, _>::from_storage_with_layout([2, 3], data, Layout::LastMajor).unwrap();
+    ///
+    /// assert_eq!(buf.get_m([1, 0]), Some(&4));
+    /// ```
+    pub fn from_storage_with_layout(size: [usize; D], storage: S, layout: Layout) -> Result<Self, anyhow::Error> {
+        let total_size = Self::calc_total_size(&size)?;
+
+        if storage.as_slice().len() != total_size {
+            return Err(anyhow!("storage length is not equal to total_size"));
+        }
+
+        Ok(Self {
+            buf: storage,
+            size,
+            stride: Self::calc_dim_stride_with_layout(&size, layout)?,
+            layout,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T, const D: usize> XDBuf<T, D> {
     /// Generate a new `XDBuf`.
     ///
     /// Allocates the specified amount of elements for each dimension and fills them with initial values.
@@ -173,6 +278,35 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// let buf = XDBuf::<i32, 3>::new(size, 0).unwrap();
     /// ```
     pub fn new(size: [usize; D], initial_value: T) -> Result<Self, anyhow::Error>
+    where
+        T: Clone,
+    {
+        Self::new_with_layout(size, initial_value, Layout::default())
+    }
+
+    /// Generate a new `XDBuf` with the specified memory `layout`.
+    ///
+    /// Allocates the specified amount of elements for each dimension and fills them with initial values.
+    ///
+    /// 指定したメモリ配置`layout`で新しい`XDBuf`を生成します。
+    ///
+    /// それぞれの次元について指定した分の要素を確保し、初期値で埋めます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Layout, XDBuf};
+    ///
+    /// let buf = XDBuf::<i32, 3>::new_with_layout([3, 4, 5], 0, Layout::LastMajor).unwrap();
+    /// assert_eq!(buf.stride(), &[20, 5, 1]);
+    /// ```
+    pub fn new_with_layout(size: [usize; D], initial_value: T, layout: Layout) -> Result<Self, anyhow::Error>
     where
         T: Clone,
     {
@@ -184,7 +318,9 @@ impl<T, const D: usize> XDBuf<T, D> {
         Ok(Self {
             buf,
             size,
-            stride: Self::calc_dim_stride(&size)?,
+            stride: Self::calc_dim_stride_with_layout(&size, layout)?,
+            layout,
+            _marker: PhantomData,
         })
     }
 
@@ -222,6 +358,38 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// let buf = XDBuf::<i32, 3>::new_with_vec(size, initial_vec).unwrap(); // panic!
     /// ```
     pub fn new_with_vec(size: [usize; D], initial_vec: Vec<T>) -> Result<Self, anyhow::Error> {
+        Self::new_with_vec_and_layout(size, initial_vec, Layout::default())
+    }
+
+    /// Generate an `XDBuf` with the specified memory `layout` from a `Vec<T>`.
+    ///
+    /// The elements of `initial_vec` are interpreted in the order given by `layout`.
+    ///
+    /// `Vec<T>`から指定したメモリ配置`layout`の`XDBuf`を生成します。
+    ///
+    /// `initial_vec`の要素は`layout`で与えられる順序で解釈されます。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the length of `initial_vec` does not match the total product of `size`.
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `initial_vec`の長さが`size`の総積と一致しない場合エラーになります。
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::{Layout, XDBuf};
+    ///
+    /// // [[1, 2, 3],
+    /// //  [4, 5, 6]] (C形式)
+    /// let buf = XDBuf::<i32, 2>::new_with_vec_and_layout([2, 3], vec![1, 2, 3, 4, 5, 6], Layout::LastMajor).unwrap();
+    ///
+    /// assert_eq!(buf.get_m([1, 0]), Some(&4));
+    /// assert_eq!(buf.get_m([0, 2]), Some(&3));
+    /// ```
+    pub fn new_with_vec_and_layout(size: [usize; D], initial_vec: Vec<T>, layout: Layout) -> Result<Self, anyhow::Error> {
         let total_size = Self::calc_total_size(&size)?;
 
         if initial_vec.len() != total_size {
@@ -231,7 +399,9 @@ impl<T, const D: usize> XDBuf<T, D> {
         Ok(Self {
             buf: initial_vec,
             size,
-            stride: Self::calc_dim_stride(&size)?,
+            stride: Self::calc_dim_stride_with_layout(&size, layout)?,
+            layout,
+            _marker: PhantomData,
         })
     }
 
@@ -266,13 +436,7 @@ impl<T, const D: usize> XDBuf<T, D> {
     where
         T: Clone,
     {
-        self.size = size;
-        self.stride = Self::calc_dim_stride(&size)?;
-
-        self.buf.clear();
-        self.buf.resize(Self::calc_total_size(&size)?, initial_value);
-
-        Ok(())
+        self.reinit_with(size, |buf, total_size, _| buf.resize(total_size, initial_value))
     }
 
     /// Initialize the buffer from `Vec<T>`.
@@ -285,10 +449,16 @@ impl<T, const D: usize> XDBuf<T, D> {
     ///
     /// # Errors
     ///
+    /// * Error if the length of `initial_vec` does not match the total product of `size`.
     /// * Error if the total product of `size` exceeds the range of `usize`.
     ///
+    /// * `initial_vec`の長さが`size`の総積と一致しない場合エラーになります。
     /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
     ///
+    /// The buffer is left unchanged on error.
+    ///
+    /// エラーの場合、バッファは変更されません。
+    ///
     /// # Example
     ///
     /// ```
@@ -300,6 +470,10 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// let initial_vec = vec![1; 6];
     /// buf.init_with_vec([1, 2, 3], initial_vec).unwrap();
     /// assert_eq!(buf.len(), 6);
+    ///
+    /// assert!(buf.init_with_vec([2, 2, 2], vec![1; 6]).is_err());
+    /// assert_eq!(buf.size(), [1, 2, 3]);
+    /// assert_eq!(buf.stride(), &[1, 1, 2]);
     /// ```
     ///
     /// ```should_panic
@@ -312,19 +486,52 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// buf.init_with_vec([1, 2, 3], initial_vec).unwrap(); // panic!
     /// ```
     pub fn init_with_vec(&mut self, size: [usize; D], mut initial_vec: Vec<T>) -> Result<(), anyhow::Error> {
-        self.size = size;
-        self.stride = Self::calc_dim_stride(&size)?;
-
         if initial_vec.len() != Self::calc_total_size(&size)? {
             return Err(anyhow!("initial_vec length is not equal to total_size"));
         }
 
+        self.reinit_with(size, |buf, _, _| buf.append(&mut initial_vec))
+    }
+
+    /// Reinitializes the buffer to `size`, reusing the allocation that `init` would reuse.
+    ///
+    /// `fill` receives the cleared storage, the total number of elements and the stride for `size`,
+    /// and must push that many elements in memory order. The size and stride are updated only after `fill` returns.
+    ///
+    /// `init`が再利用するのと同じ割り当てを再利用して、バッファを`size`に再初期化します。
+    ///
+    /// `fill`は空にされたストレージ、要素の総数、`size`に対するストライドを受け取り、その数の要素をメモリ上の順序で追加する必要があります。
+    /// サイズとストライドは`fill`が戻った後にのみ更新されます。
+    ///
+    /// If `fill` panics, the storage no longer matches the size, and the buffer must be reinitialized before it is used again.
+    ///
+    /// `fill`がパニックした場合、ストレージはサイズと一致しなくなるため、再び使用する前にバッファを再初期化する必要があります。
+    ///
+    /// # Errors
+    ///
+    /// * Error if the total product of `size` exceeds the range of `usize`.
+    ///
+    /// * `size`の総積が`usize`の範囲を超える場合エラーになります。
+    pub(crate) fn reinit_with(
+        &mut self,
+        size: [usize; D],
+        fill: impl FnOnce(&mut Vec<T>, usize, &[usize; D]),
+    ) -> Result<(), anyhow::Error> {
+        let total_size = Self::calc_total_size(&size)?;
+        let stride = Self::calc_dim_stride_with_layout(&size, self.layout)?;
+
         self.buf.clear();
-        self.buf.append(&mut initial_vec);
+        fill(&mut self.buf, total_size, &stride);
+        debug_assert_eq!(self.buf.len(), total_size);
+
+        self.size = size;
+        self.stride = stride;
 
         Ok(())
     }
+}
 
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
     /// Get a reference to the element specified by `index`.
     ///
     /// Returns `None` if `index` is out of range.
@@ -347,13 +554,75 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// assert_eq!(buf.get(60), None);
     /// ```
     pub fn get(&self, index: usize) -> Option<&T> {
-        if index >= self.buf.len() {
+        if index >= self.as_slice().len() {
             return None;
         }
 
-        self.buf.get(index)
+        self.as_slice().get(index)
     }
 
+    /// Get a reference to the element specified by `index` in array notation.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// 配列表記の`index`で指定された要素の参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let initial_vec = (1..=60).collect::<Vec<i32>>();
+    /// let buf = XDBuf::<i32, 3>::new_with_vec([3, 4, 5], initial_vec).unwrap();
+    ///
+    /// assert_eq!(buf.get_m([1, 0, 0]), Some(&2));
+    /// assert_eq!(buf.get_m([2, 3, 4]), Some(&60));
+    /// assert_eq!(buf.get_m([3, 0, 0]), None);
+    /// ```
+    pub fn get_m(&self, index: [usize; D]) -> Option<&T> {
+        self.validate_index(&index).ok()?;
+
+        self.get(self.to_scalar_index(&index).ok()?)
+    }
+
+    /// Returns an iterator over the elements of the buffer in scalar index order.
+    ///
+    /// バッファの要素をスカラーのインデックス順に走査するイテレータを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// assert_eq!(buf.iter().sum::<i32>(), 10);
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// Returns an iterator over the elements of the buffer together with their indices in array notation.
+    ///
+    /// バッファの要素を配列表記のインデックスと共に走査するイテレータを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// let items = buf.indexed_iter().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(items[2], ([0, 1], &3));
+    /// ```
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ([usize; D], &T)> + '_ {
+        self.as_slice().iter().enumerate().map(move |(i, v)| (self.to_mul_dim_index(i), v))
+    }
+}
+
+impl<T, const D: usize, S: StorageMut<T>> XDBuf<T, D, S> {
     /// Get a variable reference to the element specified by `index`.
     ///
     /// Returns `None` if `index` is out of range.
@@ -378,11 +647,7 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// assert_eq!(buf.get(0), Some(&100));
     /// ```
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        if index >= self.buf.len() {
-            return None;
-        }
-
-        Some(&mut self.buf[index])
+        self.as_mut_slice().get_mut(index)
     }
 
     /// Set `value` to the element specified by `index`.
@@ -395,15 +660,104 @@ impl<T, const D: usize> XDBuf<T, D> {
     ///
     /// * `index`が範囲外の場合エラーになります。
     pub fn set(&mut self, index: usize, value: T) -> Result<(), anyhow::Error> {
-        if index >= self.buf.len() {
-            return Err(anyhow!("index is out of range"));
-        }
-
-        self.buf[index] = value;
+        *self.get_mut(index).ok_or(anyhow!("index is out of range"))? = value;
 
         Ok(())
     }
 
+    /// Get a variable reference to the element specified by `index` in array notation.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    /// 配列表記の`index`で指定された要素の可変参照を取得します。
+    ///
+    /// `index`が範囲外の場合は`None`を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<i32, 3>::new([3, 4, 5], 0).unwrap();
+    ///
+    /// *buf.get_mut_m([2, 3, 4]).unwrap() = 100;
+    ///
+    /// assert_eq!(buf.get(59), Some(&100));
+    /// ```
+    pub fn get_mut_m(&mut self, index: [usize; D]) -> Option<&mut T> {
+        self.validate_index(&index).ok()?;
+
+        let scalar = self.to_scalar_index(&index).ok()?;
+        self.get_mut(scalar)
+    }
+
+    /// Set `value` to the element specified by `index` in array notation.
+    ///
+    /// 配列表記の`index`で指定された要素に`value`を設定します。
+    ///
+    /// # Errors
+    ///
+    /// * Error if `index` is out of range.
+    ///
+    /// * `index`が範囲外の場合エラーになります。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<i32, 3>::new([3, 4, 5], 0).unwrap();
+    ///
+    /// buf.set_m([1, 2, 3], 100).unwrap();
+    /// assert_eq!(buf.get_m([1, 2, 3]), Some(&100));
+    ///
+    /// assert!(buf.set_m([3, 0, 0], 100).is_err());
+    /// ```
+    pub fn set_m(&mut self, index: [usize; D], value: T) -> Result<(), anyhow::Error> {
+        self.validate_index(&index)?;
+
+        let scalar = self.to_scalar_index(&index)?;
+        self.set(scalar, value)
+    }
+
+    /// Returns an iterator that allows modifying each element of the buffer in scalar index order.
+    ///
+    /// バッファの要素をスカラーのインデックス順に変更しながら走査するイテレータを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// buf.iter_mut().for_each(|v| *v *= 10);
+    ///
+    /// assert_eq!(buf.get(3), Some(&40));
+    /// ```
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.as_mut_slice().iter_mut()
+    }
+
+    /// Returns the elements of the buffer as a mutable slice in memory order.
+    ///
+    /// バッファの要素をメモリ上の順序の可変なスライスとして返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<i32, 2>::new([2, 2], 0).unwrap();
+    /// buf.as_mut_slice()[3] = 4;
+    ///
+    /// assert_eq!(buf.get_m([1, 1]), Some(&4));
+    /// ```
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.buf.as_mut_slice()
+    }
+}
+
+impl<T, const D: usize, S: Storage<T>> XDBuf<T, D, S> {
     /// Generates a `Walker` with the specified `index` as its initial position.
     ///
     /// 指定された`index`を初期位置として`Walker`を生成します。
@@ -422,7 +776,7 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// let mut buf = XDBuf::<i32, 3>::new([3, 4, 5], 0).unwrap();
     /// let walker = buf.walker_from_m([0, 0, 0]).unwrap();
     /// ```
-    pub fn walker_from_m(&self, index: [usize; D]) -> Result<Walker<T, D>, anyhow::Error> {
+    pub fn walker_from_m(&self, index: [usize; D]) -> Result<Walker<'_, T, D, Self>, anyhow::Error> {
         self.validate_index(&index)?;
 
         let scalar = self.to_scalar_index(&index)?;
@@ -451,17 +805,12 @@ impl<T, const D: usize> XDBuf<T, D> {
     ///
     /// assert_eq!(walker_m.index_s(), walker_s.index_s());
     /// ```
-    pub fn walker_from_s(&self, scalar_index: usize) -> Result<Walker<T, D>, anyhow::Error> {
-        if scalar_index >= self.buf.len() {
+    pub fn walker_from_s(&self, scalar_index: usize) -> Result<Walker<'_, T, D, Self>, anyhow::Error> {
+        if scalar_index >= self.as_slice().len() {
             return Err(anyhow!("index is out of range"));
         }
 
-        Ok(
-            Walker {
-                buf_into: self,
-                current_index: scalar_index,
-            }
-        )
+        Ok(Walker::new(self, scalar_index))
     }
 
     /// Returns the number of elements in the buffer.
@@ -477,7 +826,23 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// assert_eq!(buf.len(), 60);
     /// ```
     pub fn len(&self) -> usize {
-        self.buf.len()
+        self.as_slice().len()
+    }
+
+    /// Returns `true` if the buffer contains no elements.
+    ///
+    /// バッファが要素を持たない場合に`true`を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 3>::new([3, 4, 5], 0).unwrap();
+    /// assert!(!buf.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
     }
 
     /// Returns a range of buffer indices.
@@ -493,7 +858,7 @@ impl<T, const D: usize> XDBuf<T, D> {
     /// assert_eq!(buf.idx_range(), 0..60);
     /// ```
     pub fn idx_range(&self) -> Range<usize> {
-        0..self.buf.len()
+        0..self.as_slice().len()
     }
 
     /// Returns the number of elements each buffer dimension has.
@@ -512,24 +877,60 @@ impl<T, const D: usize> XDBuf<T, D> {
         &self.stride
     }
 
-    /// Reduce buffer capacity as much as possible.
+    /// Returns the memory layout of the buffer.
     ///
-    /// バッファが確保しているメモリ容量をできるだけ縮小します。
+    /// バッファのメモリ配置を返します。
     ///
     /// # Example
     ///
     /// ```
-    /// use xdbuf::XDBuf;
+    /// use xdbuf::{Layout, XDBuf};
     ///
-    /// let mut buf = XDBuf::<i32, 3>::new([3, 4, 5], 0).unwrap();
+    /// let buf = XDBuf::<i32, 3>::new([3, 4, 5], 0).unwrap();
+    /// assert_eq!(buf.layout(), Layout::FirstMajor);
+    /// ```
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Generate a copy of the buffer with the elements rearranged into `layout`.
     ///
-    /// // 確保されている容量よりも小さい要素数をに初期化
-    /// buf.init([1, 2, 3], 1).unwrap();
+    /// Indices in array notation refer to the same elements before and after the conversion.
+    ///
+    /// 要素を`layout`の順に並べ替えたバッファの複製を生成します。
+    ///
+    /// 配列表記のインデックスは変換の前後で同じ要素を指します。
+    ///
+    /// # Example
     ///
-    /// buf.shrink_to_fit();
     /// ```
-    pub fn shrink_to_fit(&mut self) {
-        self.buf.shrink_to_fit();
+    /// use xdbuf::{Layout, XDBuf};
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([3, 2], vec![1, 2, 3, 4, 5, 6]).unwrap();
+    /// let relayout = buf.relayout(Layout::LastMajor);
+    ///
+    /// assert_eq!(relayout.iter().copied().collect::<Vec<_>>(), vec![1, 4, 2, 5, 3, 6]);
+    /// assert_eq!(relayout.get_m([2, 1]), buf.get_m([2, 1]));
+    /// ```
+    pub fn relayout(&self, layout: Layout) -> XDBuf<T, D>
+    where
+        T: Clone,
+    {
+        // サイズは検証済みなので、別の配置でもストライドの計算は失敗しない
+        let stride = Self::calc_dim_stride_with_layout(&self.size, layout).unwrap();
+        let buf = (0..self.as_slice().len()).map(|i| {
+            let index = to_mul_dim_index(i, &stride, layout);
+            let scalar = index.iter().zip(self.stride).map(|(&i, s)| i * s).sum::<usize>();
+            self.as_slice()[scalar].clone()
+        }).collect();
+
+        XDBuf {
+            buf,
+            size: self.size,
+            stride,
+            layout,
+            _marker: PhantomData,
+        }
     }
 
     /// Returns the size of each dimension of the buffer.
@@ -547,4 +948,113 @@ impl<T, const D: usize> XDBuf<T, D> {
     pub fn size(&self) -> [usize; D] {
         self.size
     }
+
+    /// Returns the elements of the buffer as a slice in memory order.
+    ///
+    /// バッファの要素をメモリ上の順序のスライスとして返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new_with_vec([2, 2], vec![1, 2, 3, 4]).unwrap();
+    /// assert_eq!(buf.as_slice(), &[1, 2, 3, 4]);
+    /// ```
+    pub fn as_slice(&self) -> &[T] {
+        self.buf.as_slice()
+    }
+
+    /// Consumes the buffer and returns the underlying storage.
+    ///
+    /// バッファを消費し、内部のストレージを返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let buf = XDBuf::<i32, 2>::new([2, 2], 1).unwrap();
+    /// assert_eq!(buf.into_storage(), vec![1; 4]);
+    /// ```
+    pub fn into_storage(self) -> S {
+        self.buf
+    }
+}
+
+impl<T, const D: usize> XDBuf<T, D> {
+    /// Returns the number of elements the buffer can hold without reallocating.
+    ///
+    /// 再割り当てせずにバッファが保持できる要素数を返します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<i32, 3>::new([3, 4, 5], 0).unwrap();
+    /// buf.init([1, 2, 3], 1).unwrap();
+    ///
+    /// assert_eq!(buf.len(), 6);
+    /// assert!(buf.capacity() >= 60);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Reduce buffer capacity as much as possible.
+    ///
+    /// バッファが確保しているメモリ容量をできるだけ縮小します。
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use xdbuf::XDBuf;
+    ///
+    /// let mut buf = XDBuf::<i32, 3>::new([3, 4, 5], 0).unwrap();
+    ///
+    /// // 確保されている容量よりも小さい要素数をに初期化
+    /// buf.init([1, 2, 3], 1).unwrap();
+    ///
+    /// buf.shrink_to_fit();
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.buf.shrink_to_fit();
+    }
+}
+
+/// Convert scalar index to array notation using `stride` laid out in `layout`.
+///
+/// `layout`で配置された`stride`を用いて、スカラーのインデックスを配列表記に変換します。
+pub(crate) fn to_mul_dim_index<const D: usize>(mut scalar: usize, stride: &[usize; D], layout: Layout) -> [usize; D] {
+    let mut index = [0; D];
+
+    for i in layout.axes_fast_to_slow::<D>().into_iter().rev() {
+        index[i] = scalar / stride[i];
+        scalar %= stride[i];
+    }
+
+    index
+}
+
+impl<T, const D: usize, S: Storage<T>> Walkable<T, D> for XDBuf<T, D, S> {
+    fn size(&self) -> [usize; D] {
+        self.size
+    }
+
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    fn to_scalar_index(&self, index: &[usize; D]) -> Result<usize, anyhow::Error> {
+        XDBuf::to_scalar_index(self, index)
+    }
+
+    fn to_mul_dim_index(&self, scalar: usize) -> [usize; D] {
+        XDBuf::to_mul_dim_index(self, scalar)
+    }
 }